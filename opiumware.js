@@ -1,7 +1,44 @@
+const Fs = require("fs");
 const Net = require("net");
+const Os = require("os");
+const Path = require("path");
 const Zlib = require("zlib");
 
-const PORTS = ["8392", "8393", "8394", "8395", "8396", "8397"];
+// Same identifier the Tauri app names its config dir after.
+const APP_IDENTIFIER = "com.ajeff.potassium-ui";
+const DEFAULT_PORTS = ["8392", "8393", "8394", "8395", "8396", "8397"];
+
+// Where the GUI keeps config.json (mirrors dirs::config_dir()).
+function configPath() {
+  const home = Os.homedir();
+  const base =
+    process.platform === "darwin"
+      ? Path.join(home, "Library", "Application Support")
+      : process.platform === "win32"
+        ? process.env.APPDATA || Path.join(home, "AppData", "Roaming")
+        : process.env.XDG_CONFIG_HOME || Path.join(home, ".config");
+  return Path.join(base, APP_IDENTIFIER, "config.json");
+}
+
+// The config's port list ([8392, ...] or { start, end }), or the
+// defaults when there is no readable config.
+function loadPorts() {
+  let spec;
+  try {
+    spec = JSON.parse(Fs.readFileSync(configPath(), "utf8")).ports;
+  } catch (_) {
+    return DEFAULT_PORTS;
+  }
+  if (Array.isArray(spec)) return [...new Set(spec.map(String))];
+  if (spec && Number.isInteger(spec.start) && Number.isInteger(spec.end)) {
+    const out = [];
+    for (let p = spec.start; p <= spec.end; p++) out.push(String(p));
+    return out;
+  }
+  return DEFAULT_PORTS;
+}
+
+const PORTS = loadPorts();
 
 function connectOnce(port, timeoutMs = 800) {
   return new Promise((resolve, reject) => {
@@ -103,6 +140,7 @@ async function attachToPort(port) {
 
 module.exports = {
   PORTS,
+  loadPorts,
   execute,
   checkPort,
   attachAny,
@@ -0,0 +1,158 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::PotassiumError;
+use crate::monitor::now_millis;
+use crate::report::ExecutionReport;
+use crate::workspace::is_script;
+
+/// Emitted with an `AutoexecRun` after each autoexec script.
+pub const AUTOEXEC_RAN: &str = "autoexec-ran";
+
+/// Runs kept in the log; older ones are dropped.
+const LOG_LIMIT: usize = 200;
+
+// ─────────────────────────────────────────────────────────────
+// Settings
+// ─────────────────────────────────────────────────────────────
+
+/// Scripts run against a port each time it becomes attached.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AutoexecSettings {
+    /// Off until the user turns it on, so upgrading never starts
+    /// running scripts on attach.
+    pub enabled: bool,
+    /// Defaults to `autoexec` inside the workspace dir.
+    pub dir: Option<PathBuf>,
+    /// Wait this long after attaching before the first script.
+    pub delay_ms: u64,
+    /// File names that are skipped. New files run by default.
+    pub disabled: BTreeSet<String>,
+}
+
+// ─────────────────────────────────────────────────────────────
+// Files
+// ─────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoexecFile {
+    pub name: String,
+    pub enabled: bool,
+}
+
+/// The scripts in `dir`, in the order they run (by file name).
+pub fn list(dir: &Path, settings: &AutoexecSettings) -> Vec<AutoexecFile> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .filter(|p| is_script(p))
+        .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .collect();
+    names.sort();
+    names
+        .into_iter()
+        .map(|name| AutoexecFile { enabled: !settings.disabled.contains(&name), name })
+        .collect()
+}
+
+// ─────────────────────────────────────────────────────────────
+// Runs
+// ─────────────────────────────────────────────────────────────
+
+/// One autoexec script sent to one port.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoexecRun {
+    pub file: String,
+    pub port: String,
+    pub timestamp: u64,
+    pub success: bool,
+    pub error: Option<PotassiumError>,
+}
+
+/// Runs every enabled script in `dir` against `port`, one after another.
+/// `execute` gets each script's path and text, to send the way the editor
+/// does (bundled, with the header); `on_run` sees each result as it comes
+/// in. Blank scripts are skipped. A failing script does not stop the ones
+/// after it.
+pub async fn run<F, Fut>(
+    dir: &Path,
+    settings: &AutoexecSettings,
+    port: &str,
+    mut execute: F,
+    on_run: impl Fn(&AutoexecRun),
+) -> Vec<AutoexecRun>
+where
+    F: FnMut(PathBuf, String) -> Fut,
+    Fut: Future<Output = Result<ExecutionReport, PotassiumError>>,
+{
+    if settings.delay_ms > 0 {
+        tokio::time::sleep(Duration::from_millis(settings.delay_ms)).await;
+    }
+
+    let mut runs = Vec::new();
+    for file in list(dir, settings).into_iter().filter(|f| f.enabled) {
+        let path = dir.join(&file.name);
+        let result = match fs::read_to_string(&path) {
+            Ok(text) if text.trim().is_empty() => continue,
+            Ok(text) => execute(path.clone(), text).await,
+            Err(e) => Err(PotassiumError::File { path: path.display().to_string(), message: e.to_string() }),
+        };
+        let error = match result {
+            Ok(report) if report.is_success() => None,
+            Ok(report) => report.last_error().cloned(),
+            Err(e) => Some(e),
+        };
+        let run = AutoexecRun {
+            file: file.name,
+            port: port.to_string(),
+            timestamp: now_millis(),
+            success: error.is_none(),
+            error,
+        };
+        on_run(&run);
+        runs.push(run);
+    }
+    runs
+}
+
+/// What autoexec ran where since launch, newest last. Managed as Tauri
+/// state.
+#[derive(Debug, Default)]
+pub struct AutoexecLog {
+    runs: Mutex<VecDeque<AutoexecRun>>,
+}
+
+impl AutoexecLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, run: AutoexecRun) {
+        let mut runs = self.runs.lock().unwrap_or_else(|e| e.into_inner());
+        if runs.len() == LOG_LIMIT {
+            runs.pop_front();
+        }
+        runs.push_back(run);
+    }
+
+    pub fn snapshot(&self) -> Vec<AutoexecRun> {
+        self.runs.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.runs.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
@@ -0,0 +1,209 @@
+use std::sync::Arc;
+
+use futures::future::{join_all, BoxFuture};
+use futures::FutureExt;
+use serde::Serialize;
+
+use crate::error::PotassiumError;
+use crate::report::ExecutionReport;
+use crate::syntax;
+use crate::transport::{Client, Target};
+
+/// Id of the backend used when the config does not name one.
+pub const DEFAULT_BACKEND: &str = "opiumware";
+
+// ─────────────────────────────────────────────────────────────
+// ExecutorBackend
+// ─────────────────────────────────────────────────────────────
+
+/// What a backend supports, so the UI can hide what it cannot do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    /// Can test a port without executing anything.
+    pub probe: bool,
+    /// Can execute on every port at once.
+    pub broadcast: bool,
+    /// Can read back what the listener writes.
+    pub replies: bool,
+    /// Can keep connections open between executes.
+    pub persistent_connections: bool,
+    /// Honors the configured payload codec.
+    pub codecs: bool,
+}
+
+/// A backend as listed to the frontend.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendInfo {
+    pub id: String,
+    pub name: String,
+    pub capabilities: Capabilities,
+}
+
+/// One local executor protocol.
+///
+/// Every method gets the `Client` built from the current config, which
+/// carries the host, port list, timeouts, codec and connection pool.
+/// A backend that does not speak plain TCP can still read its host and
+/// ports from it.
+pub trait ExecutorBackend: Send + Sync {
+    /// Stable id stored in the config, e.g. "opiumware".
+    fn id(&self) -> &'static str;
+
+    /// Display name for the UI.
+    fn name(&self) -> &'static str;
+
+    fn capabilities(&self) -> Capabilities;
+
+    /// The ports that currently have a listener, in list order.
+    fn discover<'a>(&'a self, client: &'a Client) -> BoxFuture<'a, Vec<String>>;
+
+    /// Succeeds if `port` has a listener.
+    fn probe<'a>(&'a self, client: &'a Client, port: &'a str) -> BoxFuture<'a, Result<(), PotassiumError>>;
+
+    /// Probes every port of `target` and reports each one, without
+    /// executing anything.
+    fn probe_target<'a>(
+        &'a self,
+        client: &'a Client,
+        target: &'a Target,
+    ) -> BoxFuture<'a, Result<ExecutionReport, PotassiumError>>;
+
+    fn execute<'a>(
+        &'a self,
+        client: &'a Client,
+        code: &'a str,
+        target: &'a Target,
+    ) -> BoxFuture<'a, Result<ExecutionReport, PotassiumError>>;
+
+    fn info(&self) -> BackendInfo {
+        BackendInfo {
+            id: self.id().to_string(),
+            name: self.name().to_string(),
+            capabilities: self.capabilities(),
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Opiumware
+// ─────────────────────────────────────────────────────────────
+
+/// Loopback TCP, one zlib-compressed script per connection.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Opiumware;
+
+impl Opiumware {
+    /// Wraps editor code for sending: plain Lua gets an `OpiumwareScript `
+    /// header, code that already has a header (`syntax::has_header`) is
+    /// sent as-is.
+    pub fn packet(code: &str) -> String {
+        let code = code.trim();
+        if code.is_empty() {
+            return String::new();
+        }
+        if syntax::has_header(code) {
+            code.to_string()
+        } else {
+            format!("{} {}", syntax::SCRIPT_HEADER, code)
+        }
+    }
+}
+
+impl ExecutorBackend for Opiumware {
+    fn id(&self) -> &'static str {
+        "opiumware"
+    }
+
+    fn name(&self) -> &'static str {
+        "Opiumware"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            probe: true,
+            broadcast: true,
+            replies: true,
+            persistent_connections: true,
+            codecs: true,
+        }
+    }
+
+    fn discover<'a>(&'a self, client: &'a Client) -> BoxFuture<'a, Vec<String>> {
+        async move {
+            let results = join_all(client.ports().iter().map(|p| client.probe(p))).await;
+            client
+                .ports()
+                .iter()
+                .zip(results)
+                .filter(|(_, r)| r.is_ok())
+                .map(|(p, _)| p.clone())
+                .collect()
+        }
+        .boxed()
+    }
+
+    fn probe<'a>(&'a self, client: &'a Client, port: &'a str) -> BoxFuture<'a, Result<(), PotassiumError>> {
+        client.probe(port).boxed()
+    }
+
+    fn probe_target<'a>(
+        &'a self,
+        client: &'a Client,
+        target: &'a Target,
+    ) -> BoxFuture<'a, Result<ExecutionReport, PotassiumError>> {
+        client.probe_target(target).boxed()
+    }
+
+    fn execute<'a>(
+        &'a self,
+        client: &'a Client,
+        code: &'a str,
+        target: &'a Target,
+    ) -> BoxFuture<'a, Result<ExecutionReport, PotassiumError>> {
+        client.execute(code, target).boxed()
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// BackendRegistry
+// ─────────────────────────────────────────────────────────────
+
+/// The backends the UI can pick from. Managed as Tauri state.
+pub struct BackendRegistry {
+    backends: Vec<Arc<dyn ExecutorBackend>>,
+}
+
+impl Default for BackendRegistry {
+    /// A registry with every built-in backend.
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(Opiumware);
+        registry
+    }
+}
+
+impl BackendRegistry {
+    pub fn empty() -> Self {
+        Self { backends: Vec::new() }
+    }
+
+    /// Adds `backend`, replacing any backend with the same id.
+    pub fn register(&mut self, backend: impl ExecutorBackend + 'static) {
+        self.backends.retain(|b| b.id() != backend.id());
+        self.backends.push(Arc::new(backend));
+    }
+
+    pub fn get(&self, id: &str) -> Result<Arc<dyn ExecutorBackend>, PotassiumError> {
+        self.backends
+            .iter()
+            .find(|b| b.id() == id)
+            .cloned()
+            .ok_or_else(|| PotassiumError::UnknownBackend { id: id.to_string() })
+    }
+
+    pub fn list(&self) -> Vec<BackendInfo> {
+        self.backends.iter().map(|b| b.info()).collect()
+    }
+}
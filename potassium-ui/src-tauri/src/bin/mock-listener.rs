@@ -0,0 +1,130 @@
+//! Fake Opiumware listener for UI work and manual testing.
+//!
+//!   cargo run --bin mock-listener -- [options]
+//!
+//!   --host ADDR          address to bind (default 127.0.0.1)
+//!   --ports LIST         comma-separated ports (default: the configured ports)
+//!   --refuse PORT        hold PORT without listening so connects are refused
+//!   --slow PORT:MS       wait MS before accepting each connection
+//!   --reset PORT:BYTES   reset the connection after BYTES bytes
+//!   --reply [PORT:]TEXT  write TEXT back after each script
+//!   --zlib-reply         compress replies with zlib
+//!
+//! Every option that takes a PORT may be repeated, and PORT must be one
+//! of the ports being served.
+
+use std::collections::BTreeMap;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use potassium_ui_lib::config::{default_config_path, Config};
+use potassium_ui_lib::mock::{MockBehavior, MockListener};
+use potassium_ui_lib::transport::PORTS;
+
+fn usage() -> ExitCode {
+    eprintln!(
+        "usage: mock-listener [--host ADDR] [--ports LIST] [--refuse PORT] [--slow PORT:MS]\n\
+         \x20                    [--reset PORT:BYTES] [--reply [PORT:]TEXT] [--zlib-reply]"
+    );
+    ExitCode::from(2)
+}
+
+fn split_port(arg: &str) -> Option<(u16, &str)> {
+    let (port, rest) = arg.split_once(':')?;
+    Some((port.parse().ok()?, rest))
+}
+
+/// The ports from the user's config, or the built-in range if there is
+/// no usable config.
+fn default_ports() -> Vec<u16> {
+    let configured = default_config_path().and_then(|path| Config::read(&path).ok()).map(|c| c.port_list());
+    let ports = configured.unwrap_or_else(|| PORTS.iter().map(|p| p.to_string()).collect());
+    ports.iter().filter_map(|p| p.parse().ok()).collect()
+}
+
+fn parse(args: &[String]) -> Option<(String, Vec<(u16, MockBehavior)>)> {
+    let mut host = "127.0.0.1".to_string();
+    let mut ports = default_ports();
+    let mut behaviors: BTreeMap<u16, MockBehavior> = BTreeMap::new();
+    let mut default_reply = None;
+    let mut zlib_reply = false;
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--zlib-reply" => zlib_reply = true,
+            "--host" => host = args.next()?.clone(),
+            "--ports" => {
+                ports = args.next()?.split(',').map(|p| p.trim().parse().ok()).collect::<Option<_>>()?;
+            }
+            "--refuse" => behaviors.entry(args.next()?.parse().ok()?).or_default().refuse = true,
+            "--slow" => {
+                let (port, ms) = split_port(args.next()?)?;
+                behaviors.entry(port).or_default().accept_delay = Some(Duration::from_millis(ms.parse().ok()?));
+            }
+            "--reset" => {
+                let (port, bytes) = split_port(args.next()?)?;
+                behaviors.entry(port).or_default().reset_after = Some(bytes.parse().ok()?);
+            }
+            "--reply" => {
+                let arg = args.next()?;
+                match split_port(arg) {
+                    Some((port, text)) => behaviors.entry(port).or_default().reply = Some(text.to_string()),
+                    None => default_reply = Some(arg.clone()),
+                }
+            }
+            _ => return None,
+        }
+    }
+
+    if let Some(port) = behaviors.keys().find(|p| !ports.contains(p)) {
+        eprintln!("mock-listener: port {} is not in --ports", port);
+        return None;
+    }
+
+    let specs = ports
+        .iter()
+        .map(|port| {
+            let mut behavior = behaviors.get(port).cloned().unwrap_or_default();
+            if behavior.reply.is_none() {
+                behavior.reply = default_reply.clone();
+            }
+            behavior.compress_reply = zlib_reply;
+            (*port, behavior)
+        })
+        .collect();
+    Some((host, specs))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        usage();
+        return ExitCode::SUCCESS;
+    }
+    let Some((host, specs)) = parse(&args) else {
+        return usage();
+    };
+
+    let mock = MockListener::start_with(&host, &specs, |s| {
+        let kind = if s.inflated { "zlib" } else { "raw" };
+        println!("[mock] port {} received {} bytes ({}):\n{}\n", s.port, s.bytes, kind, s.script);
+    })
+    .await;
+
+    match mock {
+        Ok(mock) => {
+            for (port, (_, behavior)) in mock.ports().iter().zip(&specs) {
+                let state = if behavior.refuse { "refusing" } else { "listening" };
+                println!("[mock] {} on {}:{}", state, mock.host(), port);
+            }
+            std::future::pending::<()>().await;
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("[mock] failed to bind: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,391 @@
+//! Headless Potassium: executes scripts through the same transport as the
+//! UI's Execute button.
+//!
+//!   cargo run --bin potassium -- [options] <command>
+//!
+//!   exec <file|->        send a script (`-` reads stdin)
+//!   attach               print the first port with a listener
+//!   ports                list the configured ports and whether they are up
+//!   watch <file>         send the file, then send it again on every save
+//!   catalog <file|->     convert a functions.txt list (or check a catalog)
+//!                        and print it as an API catalog
+//!
+//!   --port PORT          target PORT instead of the first live port
+//!   --all                target every configured port
+//!   --json               print reports as JSON
+//!   --raw                send the file as-is, without bundling its requires or
+//!                        adding the `OpiumwareScript` header
+//!   --config PATH        config file (default: the UI's config.json)
+//!   --host ADDR          override the configured host
+//!   --toml               print the catalog as TOML instead of JSON
+//!
+//! Exits 0 on success, 1 when nothing could be reached or executed, and
+//! 2 on usage, file, config or (with `blockOnSyntaxError`) syntax errors.
+
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use serde::Serialize;
+
+use potassium_ui_lib::backend::{BackendRegistry, ExecutorBackend};
+use potassium_ui_lib::bundle::{Bundle, Script};
+use potassium_ui_lib::catalog::{ApiCatalog, CatalogFormat};
+use potassium_ui_lib::config::{default_config_path, Config};
+use potassium_ui_lib::error::PotassiumError;
+use potassium_ui_lib::report::ExecutionReport;
+use potassium_ui_lib::transport::{Client, Target};
+
+/// How often `watch` checks the file's modification time.
+const WATCH_INTERVAL: Duration = Duration::from_millis(300);
+
+const FAILED: u8 = 1;
+const USAGE: u8 = 2;
+
+fn usage() -> ExitCode {
+    eprintln!(
+        "usage: potassium [--port PORT | --all] [--json] [--raw] [--config PATH] [--host ADDR]\n\
+         \x20                exec <file|-> | attach | ports | watch <file>\n\
+         \x20      potassium [--toml] catalog <file|->"
+    );
+    ExitCode::from(USAGE)
+}
+
+// ─────────────────────────────────────────────────────────────
+// Arguments
+// ─────────────────────────────────────────────────────────────
+
+enum Command {
+    Exec(String),
+    Attach,
+    Ports,
+    Watch(PathBuf),
+    Catalog(String),
+}
+
+struct Options {
+    command: Command,
+    port: Option<String>,
+    all: bool,
+    json: bool,
+    raw: bool,
+    config: Option<PathBuf>,
+    host: Option<String>,
+    toml: bool,
+}
+
+fn parse(args: &[String]) -> Option<Options> {
+    let mut command = None;
+    let mut port = None;
+    let mut all = false;
+    let mut json = false;
+    let mut raw = false;
+    let mut config = None;
+    let mut host = None;
+    let mut toml = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--port" => port = Some(args.next()?.clone()),
+            "--all" => all = true,
+            "--json" => json = true,
+            "--raw" => raw = true,
+            "--config" => config = Some(PathBuf::from(args.next()?)),
+            "--host" => host = Some(args.next()?.clone()),
+            "--toml" => toml = true,
+            "exec" if command.is_none() => command = Some(Command::Exec(args.next()?.clone())),
+            "attach" if command.is_none() => command = Some(Command::Attach),
+            "ports" if command.is_none() => command = Some(Command::Ports),
+            "watch" if command.is_none() => command = Some(Command::Watch(PathBuf::from(args.next()?))),
+            "catalog" if command.is_none() => command = Some(Command::Catalog(args.next()?.clone())),
+            _ => return None,
+        }
+    }
+
+    if port.is_some() && all {
+        return None;
+    }
+    Some(Options { command: command?, port, all, json, raw, config, host, toml })
+}
+
+// ─────────────────────────────────────────────────────────────
+// Output
+// ─────────────────────────────────────────────────────────────
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PortStatus<'a> {
+    port: &'a str,
+    label: Option<&'a str>,
+    up: bool,
+}
+
+fn print_json(value: &impl Serialize) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("[Potassium] {}", e),
+    }
+}
+
+/// Prints `err` and returns the failure exit code.
+fn fail(err: &PotassiumError, json: bool) -> ExitCode {
+    if json {
+        print_json(&serde_json::json!({ "error": err }));
+    } else {
+        eprintln!("[Potassium] {}", err);
+    }
+    ExitCode::from(FAILED)
+}
+
+fn print_report(report: &ExecutionReport, json: bool) {
+    if json {
+        print_json(report);
+        return;
+    }
+    for warning in &report.warnings {
+        eprintln!("[Potassium] warning: {}", warning);
+    }
+    println!("{}", report.message());
+    for port in &report.ports {
+        if let Some(reply) = &port.reply {
+            println!("[{}] {}", port.port, reply.text);
+        }
+        if let Some(err) = &port.reply_error {
+            eprintln!("[Potassium] {}", err);
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Commands
+// ─────────────────────────────────────────────────────────────
+
+fn read_script(source: &str) -> io::Result<String> {
+    if source == "-" {
+        let mut code = String::new();
+        io::stdin().read_to_string(&mut code)?;
+        Ok(code)
+    } else {
+        fs::read_to_string(source)
+    }
+}
+
+/// What `exec` and `watch` send: the script bundled, with the header, the
+/// way the UI's Execute sends it. Requires resolve next to the file, or in
+/// the current dir for stdin. With `--raw` the script goes as-is. Like the
+/// UI, refuses scripts that do not parse if `blockOnSyntaxError` is set.
+fn load_script(source: &str, raw: bool, config: &Config) -> Result<Bundle, PotassiumError> {
+    let code = read_script(source).map_err(|e| PotassiumError::File { path: source.to_string(), message: e.to_string() })?;
+    let path = (source != "-").then(|| Path::new(source));
+    let root = path.and_then(Path::parent).filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let script = if raw { Script::Raw(&code) } else { Script::Source { code: &code, path, root } };
+    script.load(config.block_on_syntax_error)
+}
+
+/// `--port`, `--all`, or else the first port with a listener.
+async fn resolve_target(opts: &Options, backend: &dyn ExecutorBackend, client: &Client) -> Result<Target, PotassiumError> {
+    if let Some(port) = &opts.port {
+        return Ok(Target::Port(port.clone()));
+    }
+    if opts.all {
+        return Ok(Target::All);
+    }
+    backend
+        .discover(client)
+        .await
+        .into_iter()
+        .next()
+        .map(Target::Port)
+        .ok_or(PotassiumError::NoInstanceFound)
+}
+
+async fn exec(opts: &Options, backend: &dyn ExecutorBackend, client: &Client, script: &Bundle) -> ExitCode {
+    let target = match resolve_target(opts, backend, client).await {
+        Ok(target) => target,
+        Err(e) => return fail(&e, opts.json),
+    };
+    match backend.execute(client, &script.code, &target).await {
+        Ok(mut report) => {
+            script.annotate_replies(&mut report);
+            print_report(&report, opts.json);
+            if report.is_success() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::from(FAILED)
+            }
+        }
+        Err(e) => fail(&e, opts.json),
+    }
+}
+
+async fn attach(opts: &Options, backend: &dyn ExecutorBackend, client: &Client) -> ExitCode {
+    match backend.discover(client).await.into_iter().next() {
+        Some(port) if opts.json => {
+            print_json(&serde_json::json!({ "port": port }));
+            ExitCode::SUCCESS
+        }
+        Some(port) => {
+            println!("{}", port);
+            ExitCode::SUCCESS
+        }
+        None => fail(&PotassiumError::NoInstanceFound, opts.json),
+    }
+}
+
+async fn ports(opts: &Options, config: &Config, backend: &dyn ExecutorBackend, client: &Client) -> ExitCode {
+    let live = backend.discover(client).await;
+    let statuses: Vec<PortStatus> = client
+        .ports()
+        .iter()
+        .map(|port| PortStatus { port, label: config.label(port), up: live.contains(port) })
+        .collect();
+
+    if opts.json {
+        print_json(&statuses);
+    } else {
+        for status in &statuses {
+            let state = if status.up { "up" } else { "down" };
+            match status.label {
+                Some(label) => println!("{}  {:<4}  {}", status.port, state, label),
+                None => println!("{}  {}", status.port, state),
+            }
+        }
+    }
+    if live.is_empty() {
+        ExitCode::from(FAILED)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Sends `path` once, then again whenever its modification time changes.
+/// Runs until interrupted.
+async fn watch(opts: &Options, config: &Config, backend: &dyn ExecutorBackend, client: &Client, path: &Path) -> ExitCode {
+    let Some(mut last) = modified(path) else {
+        eprintln!("[Potassium] Cannot read {}", path.display());
+        return ExitCode::from(USAGE);
+    };
+    println!("[Potassium] Watching {}", path.display());
+
+    loop {
+        match load_script(&path.to_string_lossy(), opts.raw, config) {
+            Ok(script) if script.code.trim().is_empty() => {}
+            Ok(script) => {
+                exec(opts, backend, client, &script).await;
+            }
+            Err(e) => eprintln!("[Potassium] {}", e),
+        }
+
+        loop {
+            tokio::time::sleep(WATCH_INTERVAL).await;
+            match modified(path) {
+                Some(time) if time != last => {
+                    last = time;
+                    break;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// `.json` / `.toml` files are read as catalogs (and so validated);
+/// anything else as a legacy functions.txt list.
+fn catalog(source: &str, toml: bool) -> ExitCode {
+    let path = Path::new(source);
+    let result = match path.extension() {
+        Some(ext) if ext == "json" || ext == "toml" => ApiCatalog::load(path),
+        _ => read_script(source)
+            .map(|text| ApiCatalog::from_legacy(&text))
+            .map_err(|e| PotassiumError::File { path: source.to_string(), message: e.to_string() }),
+    };
+    match result {
+        Ok(catalog) => {
+            let format = if toml { CatalogFormat::Toml } else { CatalogFormat::Json };
+            println!("{}", catalog.to_text(format));
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("[Potassium] {}", e);
+            ExitCode::from(USAGE)
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Entry point
+// ─────────────────────────────────────────────────────────────
+
+/// `--config`, which must exist and parse, or else the UI's config if
+/// there is one. A broken file is an error rather than the defaults.
+fn load_config(opts: &Options) -> Result<Config, PotassiumError> {
+    let mut config = match (&opts.config, default_config_path()) {
+        (Some(path), _) => Config::read(path)?,
+        (None, Some(path)) if path.exists() => Config::read(&path)?,
+        (None, _) => Config::default(),
+    };
+    if let Some(host) = &opts.host {
+        config.host = host.clone();
+    }
+    config.validate()?;
+    Ok(config)
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        usage();
+        return ExitCode::SUCCESS;
+    }
+    let Some(opts) = parse(&args) else {
+        return usage();
+    };
+
+    // Needs no config or listener.
+    if let Command::Catalog(source) = &opts.command {
+        return catalog(source, opts.toml);
+    }
+
+    let config = match load_config(&opts) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("[Potassium] {}", e);
+            return ExitCode::from(USAGE);
+        }
+    };
+    let backend: Arc<dyn ExecutorBackend> = match BackendRegistry::default().get(&config.backend) {
+        Ok(backend) => backend,
+        Err(e) => {
+            eprintln!("[Potassium] {}", e);
+            return ExitCode::from(USAGE);
+        }
+    };
+    let client = config.client();
+
+    match &opts.command {
+        Command::Exec(source) => match load_script(source, opts.raw, &config) {
+            Ok(script) if script.code.trim().is_empty() => {
+                eprintln!("[Potassium] Nothing to execute");
+                ExitCode::from(USAGE)
+            }
+            Ok(script) => exec(&opts, backend.as_ref(), &client, &script).await,
+            Err(e) => {
+                eprintln!("[Potassium] {}", e);
+                ExitCode::from(USAGE)
+            }
+        },
+        Command::Attach => attach(&opts, backend.as_ref(), &client).await,
+        Command::Ports => ports(&opts, &config, backend.as_ref(), &client).await,
+        Command::Watch(path) => watch(&opts, &config, backend.as_ref(), &client, path).await,
+        Command::Catalog(_) => unreachable!("handled before loading the config"),
+    }
+}
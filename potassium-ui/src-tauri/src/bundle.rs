@@ -0,0 +1,421 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use full_moon::ast::{Call, Expression, FunctionArgs, FunctionCall, Prefix, Suffix, VarExpression};
+use full_moon::node::Node;
+use full_moon::tokenizer::TokenType;
+use full_moon::visitors::Visitor;
+use full_moon::LuaVersion;
+use serde::Serialize;
+
+use crate::backend::Opiumware;
+use crate::error::PotassiumError;
+use crate::report::ExecutionReport;
+use crate::syntax::{self, split_header};
+
+/// Tried in order when `require("./x")` has no extension, then
+/// `x/init.luau` and `x/init.lua`.
+const MODULE_EXTENSIONS: &[&str] = &["luau", "lua"];
+
+/// Defines the module table and the loader every inlined `require`
+/// is rewritten to call. Modules run once, on first require.
+const PRELUDE: &str = "\
+local __bundle_modules, __bundle_loaded = {}, {}
+local function __bundle_require(name)
+\tlocal loaded = __bundle_loaded[name]
+\tif loaded == nil then
+\t\tloaded = __bundle_modules[name]()
+\t\tif loaded == nil then
+\t\t\tloaded = true
+\t\tend
+\t\t__bundle_loaded[name] = loaded
+\tend
+\treturn loaded
+end
+";
+
+fn bundle_error(message: String) -> PotassiumError {
+    PotassiumError::Bundle { message }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Output
+// ─────────────────────────────────────────────────────────────
+
+/// Where one source file's lines sit in the bundle. Lines inside a
+/// segment map one to one onto the file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleSegment {
+    /// Module name: the path relative to the workspace dir, or the
+    /// entry script's file name.
+    pub module: String,
+    pub path: Option<String>,
+    /// 1-based line in the bundle where the file's first line is.
+    pub start_line: usize,
+    pub line_count: usize,
+}
+
+/// A bundle line traced back to its source file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceLine {
+    pub module: String,
+    pub path: Option<String>,
+    pub line: usize,
+}
+
+/// The script that is actually sent, plus the line map back to the
+/// files it was built from. The entry script is the last segment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bundle {
+    pub code: String,
+    pub segments: Vec<BundleSegment>,
+}
+
+impl Bundle {
+    /// `code` to send as-is, with nothing inlined.
+    pub fn raw(code: String) -> Self {
+        let line_count = line_count(&code);
+        Bundle { code, segments: vec![BundleSegment { module: "script".into(), path: None, start_line: 1, line_count }] }
+    }
+
+    /// Which file and line bundle line `line` (1-based) came from.
+    /// `None` for the prelude and module wrappers.
+    pub fn locate(&self, line: usize) -> Option<SourceLine> {
+        self.segments.iter().find(|s| line >= s.start_line && line < s.start_line + s.line_count).map(|s| SourceLine {
+            module: s.module.clone(),
+            path: s.path.clone(),
+            line: line - s.start_line + 1,
+        })
+    }
+
+    /// True if any module was inlined.
+    pub fn is_bundled(&self) -> bool {
+        self.segments.len() > 1
+    }
+
+    /// `text` (listener output) with each `:<line>:` that falls in a
+    /// segment followed by its file and line, e.g. `:12 (lib/util.lua:3):`.
+    /// Unchanged unless something was inlined.
+    pub fn annotate(&self, text: &str) -> String {
+        if !self.is_bundled() {
+            return text.to_string();
+        }
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(at) = rest.find(':') {
+            out.push_str(&rest[..=at]);
+            rest = &rest[at + 1..];
+            let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+            if digits == 0 || !rest[digits..].starts_with(':') {
+                continue;
+            }
+            out.push_str(&rest[..digits]);
+            if let Some(source) = rest[..digits].parse().ok().and_then(|line| self.locate(line)) {
+                out.push_str(&format!(" ({}:{})", source.module, source.line));
+            }
+            out.push(':');
+            rest = &rest[digits + 1..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// Annotates each port's reply in `report`, see `annotate`.
+    pub fn annotate_replies(&self, report: &mut ExecutionReport) {
+        if !self.is_bundled() {
+            return;
+        }
+        for reply in report.ports.iter_mut().filter_map(|port| port.reply.as_mut()) {
+            reply.text = self.annotate(&reply.text);
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Requires
+// ─────────────────────────────────────────────────────────────
+
+/// A `require("./x")` call: byte span of `require(...)`, the path
+/// string and the line it is on.
+#[derive(Debug)]
+struct Require {
+    start: usize,
+    end: usize,
+    spec: String,
+    line: usize,
+}
+
+/// Finds `require` calls whose only argument is a string starting with
+/// `./` or `../`. Other requires (instances, asset ids) are left alone.
+#[derive(Debug, Default)]
+struct Requires {
+    found: Vec<Require>,
+}
+
+impl Requires {
+    fn check<'a>(&mut self, prefix: &Prefix, mut suffixes: impl Iterator<Item = &'a Suffix>) {
+        let Prefix::Name(name) = prefix else {
+            return;
+        };
+        let Some(call @ Suffix::Call(Call::AnonymousCall(args))) = suffixes.next() else {
+            return;
+        };
+        if name.token().to_string() != "require" {
+            return;
+        }
+        let string = match &**args {
+            FunctionArgs::String(token) => token,
+            FunctionArgs::Parentheses { arguments, .. } if arguments.len() == 1 => match arguments.iter().next() {
+                Some(Expression::String(token)) => token,
+                _ => return,
+            },
+            _ => return,
+        };
+        let TokenType::StringLiteral { literal, .. } = string.token_type() else {
+            return;
+        };
+        if !(literal.starts_with("./") || literal.starts_with("../")) {
+            return;
+        }
+        let start = name.token().start_position();
+        let Some(end) = call.end_position() else {
+            return;
+        };
+        self.found.push(Require { start: start.bytes(), end: end.bytes(), spec: literal.to_string(), line: start.line() });
+    }
+}
+
+impl Visitor for Requires {
+    fn visit_function_call(&mut self, node: &FunctionCall) {
+        self.check(node.prefix(), node.suffixes());
+    }
+
+    fn visit_var_expression(&mut self, node: &VarExpression) {
+        self.check(node.prefix(), node.suffixes());
+    }
+}
+
+/// `spec` relative to `dir`: the file as named, with each extension
+/// added, then as a folder with an init file.
+fn resolve(dir: &Path, spec: &str) -> Option<PathBuf> {
+    let target = dir.join(spec);
+    let with_ext = MODULE_EXTENSIONS.iter().map(|ext| {
+        let mut path = target.clone().into_os_string();
+        path.push(format!(".{}", ext));
+        PathBuf::from(path)
+    });
+    let init = MODULE_EXTENSIONS.iter().map(|ext| target.join(format!("init.{}", ext)));
+    std::iter::once(target.clone())
+        .chain(with_ext)
+        .chain(init)
+        .find(|path| path.is_file())
+        .and_then(|path| fs::canonicalize(path).ok())
+}
+
+// ─────────────────────────────────────────────────────────────
+// Bundler
+// ─────────────────────────────────────────────────────────────
+
+struct Module {
+    name: String,
+    path: PathBuf,
+    source: String,
+}
+
+struct Bundler<'a> {
+    root: &'a Path,
+    /// Modules in the order they were finished, dependencies first.
+    modules: Vec<Module>,
+    names: HashMap<PathBuf, String>,
+    /// Files being bundled right now, for cycle detection.
+    stack: Vec<(PathBuf, String)>,
+}
+
+impl Bundler<'_> {
+    fn name_of(&self, path: &Path) -> String {
+        match path.strip_prefix(self.root) {
+            Ok(rel) => rel.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/"),
+            Err(_) => path.display().to_string(),
+        }
+    }
+
+    /// Rewrites every local require in `source` to `__bundle_require`,
+    /// bundling the modules it names. Replacements keep the line count.
+    fn rewrite(&mut self, source: &str, dir: &Path, from: &str, requires: Vec<Require>) -> Result<String, PotassiumError> {
+        let mut out = String::with_capacity(source.len());
+        let mut at = 0;
+        for require in requires {
+            let path = resolve(dir, &require.spec).ok_or_else(|| {
+                bundle_error(format!("{} (required at {}:{}) was not found", require.spec, from, require.line))
+            })?;
+            let name = self.module(path)?;
+            let span = &source[require.start..require.end];
+            out.push_str(&source[at..require.start]);
+            out.push_str(&format!("__bundle_require({:?})", name));
+            out.push_str(&"\n".repeat(span.matches('\n').count()));
+            at = require.end;
+        }
+        out.push_str(&source[at..]);
+        Ok(out)
+    }
+
+    /// Bundles the module at `path` (canonical) once and returns its name.
+    fn module(&mut self, path: PathBuf) -> Result<String, PotassiumError> {
+        let name = self.name_of(&path);
+        if let Some(i) = self.stack.iter().position(|(p, _)| *p == path) {
+            let chain: Vec<&str> = self.stack[i..].iter().map(|(_, n)| n.as_str()).chain([name.as_str()]).collect();
+            return Err(bundle_error(format!("require cycle: {}", chain.join(" → "))));
+        }
+        if let Some(name) = self.names.get(&path) {
+            return Ok(name.clone());
+        }
+
+        let source = fs::read_to_string(&path).map_err(|e| bundle_error(format!("{}: {}", name, e)))?;
+        // Only the entry keeps a header; a module's is blanked out so its
+        // lines stay where they were.
+        let source = match syntax::lua_source(&source) {
+            Some(lua) => lua.into_owned(),
+            None => return Err(bundle_error(format!("{} is an {} script, not a module", name, syntax::SETTING_HEADER))),
+        };
+        if let Some(d) = syntax::check(&source).into_iter().next() {
+            return Err(bundle_error(format!("{}:{}:{}: {}", name, d.line, d.column, d.message)));
+        }
+        let requires = find_requires(&source).unwrap_or_default();
+
+        self.stack.push((path.clone(), name.clone()));
+        let dir = path.parent().unwrap_or(self.root).to_path_buf();
+        let source = self.rewrite(&source, &dir, &name, requires);
+        self.stack.pop();
+        let source = source?;
+
+        self.names.insert(path.clone(), name.clone());
+        self.modules.push(Module { name: name.clone(), path, source });
+        Ok(name)
+    }
+}
+
+/// The local requires in `source`, in order, or `None` if it does not
+/// parse.
+fn find_requires(source: &str) -> Option<Vec<Require>> {
+    let result = full_moon::parse_fallible(source, LuaVersion::luau());
+    if !result.errors().is_empty() {
+        return None;
+    }
+    let mut requires = Requires::default();
+    requires.visit_ast(result.ast());
+    requires.found.sort_by_key(|r| r.start);
+    requires.found.dedup_by_key(|r| r.start);
+    Some(requires.found)
+}
+
+fn line_count(text: &str) -> usize {
+    text.lines().count().max(1)
+}
+
+/// Inlines the modules `code` requires with `require("./x")` into one
+/// chunk. Paths are relative to the requiring file; the entry script's
+/// are relative to `path`'s folder, or `root` (the workspace dir) when
+/// it has no file. Scripts without local requires, or that do not
+/// parse, come back unchanged.
+pub fn bundle(code: &str, path: Option<&Path>, root: &Path) -> Result<Bundle, PotassiumError> {
+    let path = path.map(|p| fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf()));
+    let root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    let mut bundler = Bundler { root: &root, modules: Vec::new(), names: HashMap::new(), stack: Vec::new() };
+    let entry_name = match &path {
+        Some(p) => bundler.name_of(p),
+        None => "script".to_string(),
+    };
+    let unchanged = || Bundle {
+        code: code.to_string(),
+        segments: vec![BundleSegment {
+            module: entry_name.clone(),
+            path: path.as_ref().map(|p| p.display().to_string()),
+            start_line: 1,
+            line_count: line_count(code),
+        }],
+    };
+
+    let Some((header, lua)) = split_header(code) else {
+        return Ok(unchanged());
+    };
+    let requires = match find_requires(lua) {
+        Some(requires) if !requires.is_empty() => requires,
+        _ => return Ok(unchanged()),
+    };
+
+    if let Some(p) = &path {
+        bundler.stack.push((p.clone(), entry_name.clone()));
+    }
+    let dir = path.as_ref().and_then(|p| p.parent()).unwrap_or(&root).to_path_buf();
+    let entry = bundler.rewrite(lua, &dir, &entry_name, requires)?;
+
+    // The header goes on the first line. The lines it took in the editor
+    // (blank ones after it included) are kept in front of the entry, so
+    // entry lines still match the editor's.
+    let header_lines = header.matches('\n').count();
+    let mut out = match header.trim() {
+        "" => String::new(),
+        header => format!("{}\n", header),
+    };
+    out.push_str(PRELUDE);
+    let mut segments = Vec::new();
+    let next_line = |out: &str| out.matches('\n').count() + 1;
+    for module in &bundler.modules {
+        out.push_str(&format!("__bundle_modules[{:?}] = function(...)\n", module.name));
+        segments.push(BundleSegment {
+            module: module.name.clone(),
+            path: Some(module.path.display().to_string()),
+            start_line: next_line(&out),
+            line_count: line_count(&module.source),
+        });
+        out.push_str(&module.source);
+        if !module.source.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push_str("end\n");
+    }
+    segments.push(BundleSegment {
+        module: entry_name.clone(),
+        path: path.as_ref().map(|p| p.display().to_string()),
+        start_line: next_line(&out),
+        line_count: header_lines + line_count(&entry),
+    });
+    out.push_str(&"\n".repeat(header_lines));
+    out.push_str(&entry);
+    Ok(Bundle { code: out, segments })
+}
+
+/// What executing `code` sends: its bundle, with the header added by
+/// `Opiumware::packet`. A bundle starts with its header or the prelude,
+/// so adding the header does not move the lines the segments point at.
+pub fn prepare(code: &str, path: Option<&Path>, root: &Path) -> Result<Bundle, PotassiumError> {
+    let bundle = bundle(code, path, root)?;
+    Ok(Bundle { code: Opiumware::packet(&bundle.code), ..bundle })
+}
+
+/// A script about to be executed, from the UI or the CLI.
+pub enum Script<'a> {
+    /// Editor code; requires resolve next to `path`, or in `root`.
+    Source { code: &'a str, path: Option<&'a Path>, root: &'a Path },
+    /// Sent as-is (`--raw`, the control API's "raw").
+    Raw(&'a str),
+}
+
+impl Script<'_> {
+    /// What gets sent: `prepare`d, or as-is if raw. With `check_syntax`
+    /// (`blockOnSyntaxError`) a script that does not parse is an error.
+    pub fn load(self, check_syntax: bool) -> Result<Bundle, PotassiumError> {
+        let bundle = match self {
+            Script::Source { code, path, root } => prepare(code, path, root)?,
+            Script::Raw(code) => Bundle::raw(code.to_string()),
+        };
+        if check_syntax {
+            syntax::ensure_valid(&bundle.code)?;
+        }
+        Ok(bundle)
+    }
+}
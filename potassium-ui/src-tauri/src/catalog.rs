@@ -0,0 +1,182 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::PotassiumError;
+
+/// Newest catalog format this build reads. Bump when a change would make
+/// older builds misread a catalog.
+pub const CATALOG_VERSION: u32 = 1;
+
+/// A user catalog in the app config dir, if present, replaces the
+/// built-in one. `.toml` is read as TOML.
+pub const CATALOG_FILES: &[&str] = &["api-catalog.json", "api-catalog.toml"];
+
+/// The catalog shipped with the app. The frontend fetches the same file
+/// when it runs without Tauri.
+pub const BUILTIN_CATALOG: &str = include_str!("../../src/api-catalog.json");
+
+fn catalog_error(path: &str, e: impl ToString) -> PotassiumError {
+    PotassiumError::File { path: path.to_string(), message: e.to_string() }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Format
+// ─────────────────────────────────────────────────────────────
+
+/// The executor API the editor completes and documents.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiCatalog {
+    pub version: u32,
+    #[serde(default)]
+    pub entries: Vec<ApiEntry>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ApiEntry {
+    pub name: String,
+    /// Other names for the same function, e.g. `http_request` for `request`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// Library table the entry lives in (`debug`, `crypt`, ...).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// e.g. `hookfunction(target: function, hook: function): function`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<ApiParam>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub returns: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ApiParam {
+    pub name: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatalogFormat {
+    Json,
+    Toml,
+}
+
+impl CatalogFormat {
+    /// TOML for `.toml` files, JSON for anything else.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => CatalogFormat::Toml,
+            _ => CatalogFormat::Json,
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// ApiCatalog
+// ─────────────────────────────────────────────────────────────
+
+impl ApiCatalog {
+    /// Parses and validates a catalog. `source` names it in errors.
+    pub fn parse(text: &str, format: CatalogFormat, source: &str) -> Result<Self, PotassiumError> {
+        let catalog: ApiCatalog = match format {
+            CatalogFormat::Json => serde_json::from_str(text).map_err(|e| catalog_error(source, e))?,
+            CatalogFormat::Toml => toml::from_str(text).map_err(|e| catalog_error(source, e))?,
+        };
+        catalog.validate().map_err(|message| catalog_error(source, message))?;
+        Ok(catalog)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, PotassiumError> {
+        let source = path.display().to_string();
+        let text = fs::read_to_string(path).map_err(|e| catalog_error(&source, e))?;
+        Self::parse(&text, CatalogFormat::from_path(path), &source)
+    }
+
+    /// The catalog shipped with the app (`src/api-catalog.json`).
+    pub fn builtin() -> Self {
+        Self::parse(BUILTIN_CATALOG, CatalogFormat::Json, "api-catalog.json").expect("built-in API catalog is valid")
+    }
+
+    /// Converts a legacy `functions.txt` list: one name per line,
+    /// optionally followed by ` - `, `:` or `|` and a description. Blank
+    /// lines and `#` / `//` comments are skipped; repeats are merged.
+    /// Dotted names (`debug.getinfo`) get their namespace.
+    pub fn from_legacy(text: &str) -> Self {
+        let mut seen = BTreeSet::new();
+        let mut entries = Vec::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+                continue;
+            }
+            let (name, description) = match line.find(['-', ':', '|']) {
+                Some(at) => (line[..at].trim(), Some(line[at + 1..].trim()).filter(|d| !d.is_empty())),
+                None => (line, None),
+            };
+            let valid = name.split('.').all(|part| {
+                part.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                    && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            });
+            if !valid || !seen.insert(name.to_string()) {
+                continue;
+            }
+            entries.push(ApiEntry {
+                name: name.to_string(),
+                namespace: name.rsplit_once('.').map(|(ns, _)| ns.to_string()),
+                description: description.map(str::to_string),
+                ..Default::default()
+            });
+        }
+        entries.sort_by_key(|e| e.name.to_lowercase());
+        ApiCatalog { version: CATALOG_VERSION, entries }
+    }
+
+    pub fn to_text(&self, format: CatalogFormat) -> String {
+        match format {
+            CatalogFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default(),
+            CatalogFormat::Toml => toml::to_string_pretty(self).unwrap_or_default(),
+        }
+    }
+
+    /// The entry called `name`, by name or alias.
+    pub fn lookup(&self, name: &str) -> Option<&ApiEntry> {
+        self.entries.iter().find(|e| e.name == name || e.aliases.iter().any(|a| a == name))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.version == 0 || self.version > CATALOG_VERSION {
+            return Err(format!(
+                "catalog version {} is not supported (this build reads up to {})",
+                self.version, CATALOG_VERSION
+            ));
+        }
+        let mut names = BTreeSet::new();
+        for entry in &self.entries {
+            if entry.name.trim().is_empty() {
+                return Err("every entry needs a name".into());
+            }
+            for name in std::iter::once(&entry.name).chain(&entry.aliases) {
+                if !names.insert(name.as_str()) {
+                    return Err(format!("{} is defined more than once", name));
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+use std::fmt;
+use std::io::Write;
+use std::sync::Arc;
+
+use flate2::write::{GzEncoder, ZlibEncoder};
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::error::PotassiumError;
+
+/// Compression level used when none is configured (flate2's default).
+pub const DEFAULT_LEVEL: u32 = 6;
+
+// ─────────────────────────────────────────────────────────────
+// Codec
+// ─────────────────────────────────────────────────────────────
+
+/// Turns a script into the bytes written to a listener.
+pub trait Codec: fmt::Debug + Send + Sync {
+    fn name(&self) -> &'static str;
+    fn encode(&self, script: &[u8]) -> Result<Vec<u8>, PotassiumError>;
+}
+
+/// A codec `LengthPrefixed` can wrap, and the name the framed
+/// version goes by.
+pub trait Unframed: Codec {
+    const FRAMED_NAME: &'static str;
+}
+
+/// Zlib stream, no framing. What Opiumware expects.
+#[derive(Debug, Clone, Copy)]
+pub struct Zlib {
+    pub level: u32,
+}
+
+impl Default for Zlib {
+    fn default() -> Self {
+        Self { level: DEFAULT_LEVEL }
+    }
+}
+
+impl Unframed for Zlib {
+    const FRAMED_NAME: &'static str = "zlib+length";
+}
+
+impl Codec for Zlib {
+    fn name(&self) -> &'static str {
+        "zlib"
+    }
+
+    fn encode(&self, script: &[u8]) -> Result<Vec<u8>, PotassiumError> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::new(self.level));
+        encoder
+            .write_all(script)
+            .and_then(|_| encoder.finish())
+            .map_err(|e| PotassiumError::CompressionFailed { message: e.to_string() })
+    }
+}
+
+/// Gzip stream, no framing.
+#[derive(Debug, Clone, Copy)]
+pub struct Gzip {
+    pub level: u32,
+}
+
+impl Unframed for Gzip {
+    const FRAMED_NAME: &'static str = "gzip+length";
+}
+
+impl Codec for Gzip {
+    fn name(&self) -> &'static str {
+        "gzip"
+    }
+
+    fn encode(&self, script: &[u8]) -> Result<Vec<u8>, PotassiumError> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::new(self.level));
+        encoder
+            .write_all(script)
+            .and_then(|_| encoder.finish())
+            .map_err(|e| PotassiumError::CompressionFailed { message: e.to_string() })
+    }
+}
+
+/// The script's UTF-8 bytes as-is.
+#[derive(Debug, Clone, Copy)]
+pub struct Raw;
+
+impl Unframed for Raw {
+    const FRAMED_NAME: &'static str = "raw+length";
+}
+
+impl Codec for Raw {
+    fn name(&self) -> &'static str {
+        "raw"
+    }
+
+    fn encode(&self, script: &[u8]) -> Result<Vec<u8>, PotassiumError> {
+        Ok(script.to_vec())
+    }
+}
+
+/// Prefixes another codec's output with its length as a big-endian u32.
+#[derive(Debug)]
+pub struct LengthPrefixed<C>(pub C);
+
+impl<C: Unframed> Codec for LengthPrefixed<C> {
+    fn name(&self) -> &'static str {
+        C::FRAMED_NAME
+    }
+
+    fn encode(&self, script: &[u8]) -> Result<Vec<u8>, PotassiumError> {
+        let body = self.0.encode(script)?;
+        let len = u32::try_from(body.len()).map_err(|_| PotassiumError::CompressionFailed {
+            message: format!("payload of {} bytes does not fit a 4-byte length prefix", body.len()),
+        })?;
+        let mut out = Vec::with_capacity(body.len() + 4);
+        out.extend_from_slice(&len.to_be_bytes());
+        out.extend_from_slice(&body);
+        Ok(out)
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Codec settings
+// ─────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CodecKind {
+    #[default]
+    Zlib,
+    Gzip,
+    Raw,
+}
+
+/// Codec choice as stored in the config.
+///
+/// In JSON: `{ "codec": "zlib", "level": 6, "lengthPrefix": false }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CodecSettings {
+    pub codec: CodecKind,
+    /// 0 (store) to 9 (smallest). Ignored by `raw`.
+    pub level: u32,
+    /// Frame the payload with a 4-byte big-endian length.
+    pub length_prefix: bool,
+}
+
+impl Default for CodecSettings {
+    fn default() -> Self {
+        Self { codec: CodecKind::Zlib, level: DEFAULT_LEVEL, length_prefix: false }
+    }
+}
+
+impl CodecSettings {
+    pub fn build(&self) -> Arc<dyn Codec> {
+        let level = self.level;
+        match (self.codec, self.length_prefix) {
+            (CodecKind::Zlib, false) => Arc::new(Zlib { level }),
+            (CodecKind::Zlib, true) => Arc::new(LengthPrefixed(Zlib { level })),
+            (CodecKind::Gzip, false) => Arc::new(Gzip { level }),
+            (CodecKind::Gzip, true) => Arc::new(LengthPrefixed(Gzip { level })),
+            (CodecKind::Raw, false) => Arc::new(Raw),
+            (CodecKind::Raw, true) => Arc::new(LengthPrefixed(Raw)),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), PotassiumError> {
+        if self.level > 9 {
+            return Err(PotassiumError::Config {
+                message: format!("Compression level must be 0-9, got {}", self.level),
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::autoexec::AutoexecSettings;
+use crate::backend::DEFAULT_BACKEND;
+use crate::codec::CodecSettings;
+use crate::control::ControlSettings;
+use crate::error::PotassiumError;
+use crate::format::FormatSettings;
+use crate::reply::ReplyOptions;
+use crate::transport::{validate_port, Client, PORTS};
+
+/// Upper bound on how many ports a config may list, so a typo like
+/// `1-65535` cannot turn every scan into a port sweep.
+pub const MAX_PORTS: usize = 64;
+
+/// Fastest allowed instance monitor interval.
+pub const MIN_MONITOR_INTERVAL_MS: u64 = 250;
+
+/// Largest reply the backend will buffer per port.
+pub const MAX_REPLY_BYTES: usize = 16 * 1024 * 1024;
+
+/// Longest the backend will wait on a reply before moving on.
+pub const MAX_REPLY_TIMEOUT_MS: u64 = 30_000;
+
+/// Same as `identifier` in `tauri.conf.json`; Tauri names the app config
+/// dir after it.
+pub const APP_IDENTIFIER: &str = "com.ajeff.potassium-ui";
+
+/// Where the GUI keeps `config.json`, for tools that run without Tauri.
+pub fn default_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(APP_IDENTIFIER).join("config.json"))
+}
+
+// ─────────────────────────────────────────────────────────────
+// Port spec
+// ─────────────────────────────────────────────────────────────
+
+/// Which ports to scan: an explicit list, or an inclusive range.
+///
+/// In JSON this is either `[8392, 8393]` or `{ "start": 8392, "end": 8397 }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PortSpec {
+    List(Vec<u16>),
+    Range { start: u16, end: u16 },
+}
+
+impl PortSpec {
+    pub fn expand(&self) -> Vec<String> {
+        match self {
+            PortSpec::List(ports) => ports.iter().map(|p| p.to_string()).collect(),
+            PortSpec::Range { start, end } => (*start..=*end).map(|p| p.to_string()).collect(),
+        }
+    }
+}
+
+impl Default for PortSpec {
+    fn default() -> Self {
+        PortSpec::List(PORTS.iter().filter_map(|p| p.parse().ok()).collect())
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Port profile
+// ─────────────────────────────────────────────────────────────
+
+/// Per-port overrides. Anything left out uses the global setting.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PortProfile {
+    pub codec: Option<CodecSettings>,
+}
+
+// ─────────────────────────────────────────────────────────────
+// Config
+// ─────────────────────────────────────────────────────────────
+
+/// Backend connection settings, persisted as JSON in the app config dir.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Config {
+    /// Id of the executor backend (see `BackendRegistry`).
+    pub backend: String,
+    /// IP address the listeners are bound to.
+    pub host: String,
+    pub ports: PortSpec,
+    /// Optional display names, keyed by port number.
+    pub labels: BTreeMap<String, String>,
+    /// How often the instance monitor re-probes the ports.
+    pub monitor_interval_ms: u64,
+    /// Reject executes on ports that are not attached instead of
+    /// only warning in the report.
+    pub require_attach: bool,
+    /// Refuse to send scripts that do not parse as Luau.
+    pub block_on_syntax_error: bool,
+    /// Keep one connection open per attached port and reconnect with
+    /// backoff, instead of connecting for every execute.
+    ///
+    /// Scripts then go back to back on one socket, and an unframed
+    /// codec gives the listener nothing to tell where one ends and the
+    /// next begins. Turn on `codec.lengthPrefix` unless the listener
+    /// only ever takes one script per connection.
+    pub persistent_connections: bool,
+    /// Read back what the listener writes after a script.
+    pub reply: ReplyOptions,
+    /// How scripts are encoded on the wire. Zlib by default.
+    pub codec: CodecSettings,
+    /// Per-port overrides, keyed by port number.
+    pub profiles: BTreeMap<String, PortProfile>,
+    /// Localhost HTTP/WebSocket API for external editors.
+    pub control: ControlSettings,
+    /// Where new tabs are saved. Defaults to `Documents/Potassium`.
+    pub workspace_dir: Option<PathBuf>,
+    /// The script library. Defaults to `Library` in the workspace dir.
+    pub library_dir: Option<PathBuf>,
+    /// Scripts run on every attach.
+    pub autoexec: AutoexecSettings,
+    /// Formatter settings, StyLua-style.
+    pub format: FormatSettings,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            backend: DEFAULT_BACKEND.to_string(),
+            host: "127.0.0.1".to_string(),
+            ports: PortSpec::default(),
+            labels: BTreeMap::new(),
+            monitor_interval_ms: 2000,
+            require_attach: false,
+            block_on_syntax_error: false,
+            persistent_connections: false,
+            reply: ReplyOptions::default(),
+            codec: CodecSettings::default(),
+            profiles: BTreeMap::new(),
+            control: ControlSettings::default(),
+            workspace_dir: None,
+            library_dir: None,
+            autoexec: AutoexecSettings::default(),
+            format: FormatSettings::default(),
+        }
+    }
+}
+
+impl Config {
+    /// The port list with ranges expanded, in scan order.
+    pub fn port_list(&self) -> Vec<String> {
+        self.ports.expand()
+    }
+
+    pub fn label(&self, port: &str) -> Option<&str> {
+        self.labels.get(port).map(String::as_str)
+    }
+
+    pub fn client(&self) -> Client {
+        let mut client = Client::new(self.host.clone(), self.port_list())
+            .with_reply(self.reply.clone())
+            .with_codec(self.codec.build());
+        for (port, profile) in &self.profiles {
+            if let Some(codec) = &profile.codec {
+                client = client.with_port_codec(port, codec.build());
+            }
+        }
+        client
+    }
+
+    /// Reads and validates the config file at `path`.
+    pub fn read(path: &Path) -> Result<Self, PotassiumError> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| PotassiumError::Config { message: format!("{}: {}", path.display(), e) })?;
+        let config: Config = serde_json::from_str(&text)
+            .map_err(|e| PotassiumError::Config { message: format!("{}: {}", path.display(), e) })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn monitor_interval(&self) -> Duration {
+        Duration::from_millis(self.monitor_interval_ms.max(MIN_MONITOR_INTERVAL_MS))
+    }
+
+    pub fn validate(&self) -> Result<(), PotassiumError> {
+        self.host.parse::<IpAddr>().map_err(|e| PotassiumError::AddrParse {
+            addr: self.host.clone(),
+            message: e.to_string(),
+        })?;
+
+        let count = match &self.ports {
+            PortSpec::List(ports) => ports.len(),
+            PortSpec::Range { start, end } if start <= end => usize::from(end - start) + 1,
+            PortSpec::Range { start, end } => {
+                return Err(PotassiumError::Config {
+                    message: format!("Port range {}-{} is backwards", start, end),
+                })
+            }
+        };
+        if count == 0 || count > MAX_PORTS {
+            return Err(PotassiumError::Config {
+                message: format!("Port list must have between 1 and {} ports", MAX_PORTS),
+            });
+        }
+        if let PortSpec::List(ports) = &self.ports {
+            if let Some(port) = ports.iter().enumerate().find_map(|(i, p)| ports[..i].contains(p).then_some(p)) {
+                return Err(PotassiumError::Config { message: format!("Port {} is listed more than once", port) });
+            }
+        }
+        for port in self.port_list() {
+            validate_port(&port)?;
+        }
+        for port in self.labels.keys() {
+            validate_port(port)?;
+        }
+        self.codec.validate()?;
+        self.format.validate()?;
+        for (port, profile) in &self.profiles {
+            validate_port(port)?;
+            if let Some(codec) = &profile.codec {
+                codec.validate()?;
+            }
+        }
+        if self.monitor_interval_ms < MIN_MONITOR_INTERVAL_MS {
+            return Err(PotassiumError::Config {
+                message: format!("monitorIntervalMs must be at least {}", MIN_MONITOR_INTERVAL_MS),
+            });
+        }
+        if self.reply.max_bytes == 0 || self.reply.max_bytes > MAX_REPLY_BYTES {
+            return Err(PotassiumError::Config {
+                message: format!("reply.maxBytes must be between 1 and {}", MAX_REPLY_BYTES),
+            });
+        }
+        if self.reply.timeout_ms > MAX_REPLY_TIMEOUT_MS {
+            return Err(PotassiumError::Config {
+                message: format!("reply.timeoutMs must be at most {}", MAX_REPLY_TIMEOUT_MS),
+            });
+        }
+        if self.control.port == 0 || self.port_list().contains(&self.control.port.to_string()) {
+            return Err(PotassiumError::Config {
+                message: format!("control.port {} must be non-zero and not one of the listener ports", self.control.port),
+            });
+        }
+        Ok(())
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// ConfigStore
+// ─────────────────────────────────────────────────────────────
+
+/// `config.json` → `config.json.bak`, next to it.
+fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// The live config plus the file it is persisted to.
+/// Managed as Tauri state so every command reads the same copy.
+#[derive(Debug)]
+pub struct ConfigStore {
+    path: PathBuf,
+    config: RwLock<Config>,
+}
+
+impl ConfigStore {
+    /// Loads `path`, falling back to defaults if it is missing or invalid.
+    /// An invalid file is moved to `config.json.bak` first, so the next
+    /// `set` does not write the defaults over the user's settings.
+    pub fn load(path: PathBuf) -> Self {
+        let config = match Config::read(&path) {
+            Ok(config) => config,
+            Err(_) if !path.exists() => Config::default(),
+            Err(e) => {
+                let backup = backup_path(&path);
+                match fs::rename(&path, &backup) {
+                    Ok(()) => eprintln!("[Potassium] Invalid config ({}), moved to {}", e, backup.display()),
+                    Err(err) => eprintln!("[Potassium] Invalid config ({}), could not back it up: {}", e, err),
+                }
+                Config::default()
+            }
+        };
+        Self { path, config: RwLock::new(config) }
+    }
+
+    pub fn get(&self) -> Config {
+        self.config.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn client(&self) -> Client {
+        self.get().client()
+    }
+
+    /// Validates, persists and applies a new config.
+    pub fn set(&self, config: Config) -> Result<Config, PotassiumError> {
+        config.validate()?;
+
+        let json = serde_json::to_string_pretty(&config)
+            .map_err(|e| PotassiumError::Config { message: e.to_string() })?;
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| PotassiumError::Config { message: e.to_string() })?;
+        }
+        fs::write(&self.path, json).map_err(|e| PotassiumError::Config { message: e.to_string() })?;
+
+        *self.config.write().unwrap_or_else(|e| e.into_inner()) = config.clone();
+        println!("[Potassium] Config saved to {}", self.path.display());
+        Ok(config)
+    }
+}
@@ -0,0 +1,402 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+
+use crate::error::PotassiumError;
+use crate::monitor::{now_millis, InstanceStatus};
+use crate::pool::ConnectionStatus;
+use crate::report::ExecutionReport;
+use crate::session::{AttachedPort, SessionInfo};
+
+/// Emitted with the `ExecutionReport` of every execute, from the UI or the
+/// control API.
+pub const EXECUTION_RESULT: &str = "execution-result";
+
+/// File in the app config dir holding the per-install control token.
+pub const TOKEN_FILE: &str = "control-token";
+
+/// Default port for the control API. Outside the Opiumware range.
+pub const DEFAULT_CONTROL_PORT: u16 = 47392;
+
+/// Events buffered per WebSocket client before the slowest one starts
+/// missing some.
+const EVENT_BUFFER: usize = 256;
+
+/// How long `stop` waits for open requests before cutting them off.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(1);
+
+// ─────────────────────────────────────────────────────────────
+// Settings
+// ─────────────────────────────────────────────────────────────
+
+/// The localhost control API for external editors. Off by default.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ControlSettings {
+    pub enabled: bool,
+    /// Port on 127.0.0.1. The API never listens on other interfaces.
+    pub port: u16,
+    /// Browser origins (e.g. `vscode-webview://...`) that may call the
+    /// API. Requests with any other `Origin` header are rejected, so web
+    /// pages cannot reach it; clients that send none are unaffected.
+    pub allowed_origins: Vec<String>,
+}
+
+impl Default for ControlSettings {
+    fn default() -> Self {
+        Self { enabled: false, port: DEFAULT_CONTROL_PORT, allowed_origins: Vec::new() }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Token
+// ─────────────────────────────────────────────────────────────
+
+/// Reads the token at `path`, creating one if the file is missing or empty.
+pub fn load_or_create_token(path: &Path) -> io::Result<String> {
+    match fs::read_to_string(path) {
+        Ok(token) if !token.trim().is_empty() => Ok(token.trim().to_string()),
+        Ok(_) => reset_token(path),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => reset_token(path),
+        Err(e) => Err(e),
+    }
+}
+
+/// Writes a fresh random token to `path` and returns it.
+pub fn reset_token(path: &Path) -> io::Result<String> {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes).map_err(io::Error::other)?;
+    let token: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, &token)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(token)
+}
+
+// ─────────────────────────────────────────────────────────────
+// Handler
+// ─────────────────────────────────────────────────────────────
+
+/// Snapshot returned by `GET /status`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlStatus {
+    pub backend: String,
+    pub session: SessionInfo,
+    pub instances: Vec<InstanceStatus>,
+    pub connections: Vec<ConnectionStatus>,
+}
+
+/// What the API does for each request. The app implements this on top of
+/// the same session and execute path as its commands.
+pub trait ControlHandler: Send + Sync {
+    fn status(&self) -> BoxFuture<'static, Result<ControlStatus, PotassiumError>>;
+
+    /// Attaches `port`, or the first port with a listener.
+    fn attach(&self, port: Option<String>) -> BoxFuture<'static, Result<AttachedPort, PotassiumError>>;
+
+    /// Executes on `port` ("ALL" for every port), or on the first attached
+    /// port. Unless `raw`, `code` is sent like editor code: bundled, with
+    /// the `OpiumwareScript` header.
+    fn execute(
+        &self,
+        code: String,
+        port: Option<String>,
+        raw: bool,
+    ) -> BoxFuture<'static, Result<ExecutionReport, PotassiumError>>;
+}
+
+// ─────────────────────────────────────────────────────────────
+// Events
+// ─────────────────────────────────────────────────────────────
+
+/// One message on the `/events` WebSocket: the app event name and its
+/// payload, the same as the frontend receives.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlEvent {
+    pub event: String,
+    pub payload: Value,
+    pub timestamp: u64,
+}
+
+// ─────────────────────────────────────────────────────────────
+// ControlServer
+// ─────────────────────────────────────────────────────────────
+
+struct Running {
+    addr: SocketAddr,
+    /// Dropped to shut the server and its WebSockets down.
+    shutdown: watch::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+#[derive(Clone)]
+struct Ctx {
+    token: Arc<str>,
+    allowed_origins: Arc<[String]>,
+    handler: Arc<dyn ControlHandler>,
+    events: broadcast::Sender<ControlEvent>,
+    shutdown: watch::Receiver<()>,
+}
+
+/// Localhost HTTP + WebSocket API so editor plugins can drive the running
+/// app. Every request needs the per-install token, either as
+/// `Authorization: Bearer <token>` or `?token=<token>` (percent-encoded),
+/// and requests from browsers need an allowlisted `Origin`.
+///
+///   GET  /status    `ControlStatus`
+///   POST /attach    `{ port? }` → `AttachedPort`
+///   POST /execute   `{ code, port?, raw? }` → `ExecutionReport`
+///   GET  /events    WebSocket of `ControlEvent`s
+///
+/// Managed as Tauri state.
+pub struct ControlServer {
+    events: broadcast::Sender<ControlEvent>,
+    running: Mutex<Option<Running>>,
+}
+
+impl Default for ControlServer {
+    fn default() -> Self {
+        Self { events: broadcast::channel(EVENT_BUFFER).0, running: Mutex::new(None) }
+    }
+}
+
+impl ControlServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forwards an app event to every connected WebSocket.
+    pub fn publish(&self, event: &str, payload: &impl Serialize) {
+        if self.events.receiver_count() == 0 {
+            return;
+        }
+        let payload = serde_json::to_value(payload).unwrap_or_default();
+        let _ = self.events.send(ControlEvent { event: event.to_string(), payload, timestamp: now_millis() });
+    }
+
+    /// Where the API is listening, if it is running.
+    pub fn address(&self) -> Option<SocketAddr> {
+        self.running.lock().unwrap_or_else(|e| e.into_inner()).as_ref().map(|r| r.addr)
+    }
+
+    /// (Re)starts the API on 127.0.0.1:`port`. Port 0 picks a free port;
+    /// the returned address has the real one.
+    pub async fn start(
+        &self,
+        port: u16,
+        token: String,
+        allowed_origins: Vec<String>,
+        handler: Arc<dyn ControlHandler>,
+    ) -> io::Result<SocketAddr> {
+        self.stop().await;
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port)).await?;
+        let addr = listener.local_addr()?;
+        let (shutdown, shutdown_rx) = watch::channel(());
+        let ctx = Ctx {
+            token: token.into(),
+            allowed_origins: allowed_origins.into(),
+            handler,
+            events: self.events.clone(),
+            shutdown: shutdown_rx.clone(),
+        };
+
+        let app = Router::new()
+            .route("/status", get(status))
+            .route("/attach", post(attach))
+            .route("/execute", post(execute))
+            .route("/events", get(events))
+            .layer(middleware::from_fn_with_state(ctx.clone(), authorize))
+            .with_state(ctx);
+
+        let mut closed = shutdown_rx;
+        let task = tokio::spawn(async move {
+            let serve = axum::serve(listener, app).with_graceful_shutdown(async move {
+                let _ = closed.changed().await;
+            });
+            if let Err(e) = serve.await {
+                eprintln!("[Potassium] Control API stopped: {}", e);
+            }
+        });
+
+        *self.running.lock().unwrap_or_else(|e| e.into_inner()) = Some(Running { addr, shutdown, task });
+        println!("[Potassium] Control API listening on {}", addr);
+        Ok(addr)
+    }
+
+    /// Stops the API and closes every WebSocket. No-op if it is not running.
+    pub async fn stop(&self) {
+        let running = self.running.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(Running { shutdown, mut task, .. }) = running {
+            drop(shutdown);
+            if tokio::time::timeout(SHUTDOWN_GRACE, &mut task).await.is_err() {
+                task.abort();
+            }
+        }
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        if let Some(running) = self.running.get_mut().unwrap_or_else(|e| e.into_inner()).take() {
+            running.task.abort();
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Routes
+// ─────────────────────────────────────────────────────────────
+
+struct ApiError(PotassiumError);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self.0 {
+            PotassiumError::Unauthorized => StatusCode::UNAUTHORIZED,
+            PotassiumError::OriginNotAllowed { .. } => StatusCode::FORBIDDEN,
+            PotassiumError::InvalidPort { .. }
+            | PotassiumError::AddrParse { .. }
+            | PotassiumError::NotAttached { .. }
+            | PotassiumError::Config { .. }
+            | PotassiumError::UnknownBackend { .. }
+            | PotassiumError::File { .. } => StatusCode::BAD_REQUEST,
+            // The script itself is at fault, not the listener.
+            PotassiumError::Syntax { .. } | PotassiumError::Bundle { .. } | PotassiumError::Format { .. } => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            PotassiumError::ConnectRefused { .. }
+            | PotassiumError::Timeout { .. }
+            | PotassiumError::WriteFailed { .. }
+            | PotassiumError::ReadFailed { .. }
+            | PotassiumError::CompressionFailed { .. }
+            | PotassiumError::NoInstanceFound
+            | PotassiumError::Io { .. } => StatusCode::BAD_GATEWAY,
+        };
+        (status, Json(self.0)).into_response()
+    }
+}
+
+/// Compares without returning early, so the time taken does not leak how
+/// much of the token matched.
+fn token_matches(given: &str, token: &str) -> bool {
+    given.len() == token.len() && given.bytes().zip(token.bytes()).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+async fn authorize(State(ctx): State<Ctx>, request: Request, next: Next) -> Response {
+    // Browsers always send `Origin` on cross-origin requests and
+    // WebSocket upgrades; editor plugins and curl do not.
+    if let Some(origin) = request.headers().get(header::ORIGIN) {
+        let origin = String::from_utf8_lossy(origin.as_bytes()).into_owned();
+        if !ctx.allowed_origins.contains(&origin) {
+            return ApiError(PotassiumError::OriginNotAllowed { origin }).into_response();
+        }
+    }
+
+    let bearer = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::to_string);
+    let query = || {
+        let Query(mut params) = Query::<HashMap<String, String>>::try_from_uri(request.uri()).ok()?;
+        params.remove("token")
+    };
+
+    match bearer.or_else(query) {
+        Some(given) if token_matches(&given, &ctx.token) => next.run(request).await,
+        _ => ApiError(PotassiumError::Unauthorized).into_response(),
+    }
+}
+
+async fn status(State(ctx): State<Ctx>) -> Result<Json<ControlStatus>, ApiError> {
+    ctx.handler.status().await.map(Json).map_err(ApiError)
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct AttachRequest {
+    port: Option<String>,
+}
+
+async fn attach(State(ctx): State<Ctx>, Json(body): Json<AttachRequest>) -> Result<Json<AttachedPort>, ApiError> {
+    ctx.handler.attach(body.port).await.map(Json).map_err(ApiError)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExecuteRequest {
+    code: String,
+    #[serde(default)]
+    port: Option<String>,
+    /// Send `code` as-is instead of bundling it and adding the
+    /// `OpiumwareScript` header like the editor does.
+    #[serde(default)]
+    raw: bool,
+}
+
+async fn execute(State(ctx): State<Ctx>, Json(body): Json<ExecuteRequest>) -> Result<Json<ExecutionReport>, ApiError> {
+    ctx.handler.execute(body.code, body.port, body.raw).await.map(Json).map_err(ApiError)
+}
+
+async fn events(State(ctx): State<Ctx>, ws: WebSocketUpgrade) -> Response {
+    let events = ctx.events.subscribe();
+    ws.on_upgrade(move |socket| stream_events(socket, events, ctx.shutdown))
+}
+
+async fn stream_events(
+    mut socket: WebSocket,
+    mut events: broadcast::Receiver<ControlEvent>,
+    mut shutdown: watch::Receiver<()>,
+) {
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => {
+                    let json = serde_json::to_string(&event).unwrap_or_default();
+                    if socket.send(Message::Text(json.into())).await.is_err() {
+                        return;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => continue,
+            },
+            _ = shutdown.changed() => {
+                let _ = socket.send(Message::Close(None)).await;
+                return;
+            }
+        }
+    }
+}
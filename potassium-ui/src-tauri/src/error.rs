@@ -0,0 +1,160 @@
+use std::fmt;
+use std::io;
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+// ─────────────────────────────────────────────────────────────
+// PotassiumError
+// ─────────────────────────────────────────────────────────────
+
+/// Everything that can go wrong between the UI and an Opiumware listener.
+///
+/// Serializes as `{ kind, message, port }` so the frontend can switch on
+/// `kind` instead of parsing the message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PotassiumError {
+    /// The host/port pair is not a valid socket address.
+    AddrParse { addr: String, message: String },
+    /// Nothing is listening on the port.
+    ConnectRefused { port: String },
+    /// The connect attempt did not finish in time.
+    Timeout { port: String },
+    /// Connected, but the listener went away while we were writing.
+    WriteFailed { port: String, message: String },
+    /// The script was sent, but reading the listener's reply failed.
+    ReadFailed { port: String, message: String },
+    /// The script could not be compressed.
+    CompressionFailed { message: String },
+    /// The port is not a number in 1..=65535.
+    InvalidPort { port: String },
+    /// No port in the list has a listener.
+    NoInstanceFound,
+    /// The execute target is not attached and the config requires it.
+    NotAttached { port: String },
+    /// Any other I/O failure while connecting.
+    Io { port: String, message: String },
+    /// The backend config is invalid or could not be saved.
+    Config { message: String },
+    /// The config names an executor backend that is not registered.
+    UnknownBackend { id: String },
+    /// A control API request without a valid token.
+    Unauthorized,
+    /// A control API request from a browser origin that is not allowlisted.
+    OriginNotAllowed { origin: String },
+    /// Reading, writing or renaming a script file failed.
+    File { path: String, message: String },
+    /// The script does not parse and the config blocks such executes.
+    Syntax { line: usize, column: usize, message: String },
+    /// The formatter could not produce equivalent code.
+    Format { message: String },
+    /// A `require("./x")` could not be inlined before sending.
+    Bundle { message: String },
+}
+
+impl PotassiumError {
+    /// Classifies an error returned by `TcpStream::connect_timeout`.
+    pub fn from_connect(port: &str, e: &io::Error) -> Self {
+        let port = port.to_string();
+        match e.kind() {
+            io::ErrorKind::ConnectionRefused => PotassiumError::ConnectRefused { port },
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => PotassiumError::Timeout { port },
+            _ => PotassiumError::Io { port, message: e.to_string() },
+        }
+    }
+
+    /// Stable identifier used as the `kind` field when serialized.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PotassiumError::AddrParse { .. } => "addrParse",
+            PotassiumError::ConnectRefused { .. } => "connectRefused",
+            PotassiumError::Timeout { .. } => "timeout",
+            PotassiumError::WriteFailed { .. } => "writeFailed",
+            PotassiumError::ReadFailed { .. } => "readFailed",
+            PotassiumError::CompressionFailed { .. } => "compressionFailed",
+            PotassiumError::InvalidPort { .. } => "invalidPort",
+            PotassiumError::NoInstanceFound => "noInstanceFound",
+            PotassiumError::NotAttached { .. } => "notAttached",
+            PotassiumError::Io { .. } => "io",
+            PotassiumError::Config { .. } => "config",
+            PotassiumError::UnknownBackend { .. } => "unknownBackend",
+            PotassiumError::Unauthorized => "unauthorized",
+            PotassiumError::OriginNotAllowed { .. } => "originNotAllowed",
+            PotassiumError::File { .. } => "file",
+            PotassiumError::Syntax { .. } => "syntax",
+            PotassiumError::Format { .. } => "format",
+            PotassiumError::Bundle { .. } => "bundle",
+        }
+    }
+
+    /// The port the error happened on, if it is tied to one.
+    pub fn port(&self) -> Option<&str> {
+        match self {
+            PotassiumError::ConnectRefused { port }
+            | PotassiumError::Timeout { port }
+            | PotassiumError::WriteFailed { port, .. }
+            | PotassiumError::ReadFailed { port, .. }
+            | PotassiumError::InvalidPort { port }
+            | PotassiumError::NotAttached { port }
+            | PotassiumError::Io { port, .. } => Some(port),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PotassiumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PotassiumError::AddrParse { addr, message } => {
+                write!(f, "Invalid address {}: {}", addr, message)
+            }
+            PotassiumError::ConnectRefused { port } => {
+                write!(f, "Failed to connect to port {}: connection refused", port)
+            }
+            PotassiumError::Timeout { port } => {
+                write!(f, "Failed to connect to port {}: timed out", port)
+            }
+            PotassiumError::WriteFailed { port, message } => {
+                write!(f, "Error sending script to port {}: {}", port, message)
+            }
+            PotassiumError::ReadFailed { port, message } => {
+                write!(f, "Error reading reply from port {}: {}", port, message)
+            }
+            PotassiumError::CompressionFailed { message } => {
+                write!(f, "Error compressing script: {}", message)
+            }
+            PotassiumError::InvalidPort { port } => write!(f, "Invalid port: {}", port),
+            PotassiumError::NoInstanceFound => {
+                write!(f, "No Opiumware instance found on any port")
+            }
+            PotassiumError::NotAttached { port } => write!(f, "Not attached to port {}", port),
+            PotassiumError::Io { port, message } => {
+                write!(f, "Failed to connect to port {}: {}", port, message)
+            }
+            PotassiumError::Config { message } => write!(f, "Invalid config: {}", message),
+            PotassiumError::UnknownBackend { id } => write!(f, "Unknown executor backend: {}", id),
+            PotassiumError::Unauthorized => write!(f, "Missing or invalid control token"),
+            PotassiumError::OriginNotAllowed { origin } => {
+                write!(f, "Origin {} is not allowed to use the control API", origin)
+            }
+            PotassiumError::File { path, message } => write!(f, "{}: {}", path, message),
+            PotassiumError::Syntax { line, column, message } => {
+                write!(f, "Syntax error at line {}, column {}: {}", line, column, message)
+            }
+            PotassiumError::Format { message } => write!(f, "Could not format script: {}", message),
+            PotassiumError::Bundle { message } => write!(f, "Could not bundle script: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for PotassiumError {}
+
+impl Serialize for PotassiumError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("PotassiumError", 3)?;
+        s.serialize_field("kind", self.kind())?;
+        s.serialize_field("message", &self.to_string())?;
+        s.serialize_field("port", &self.port())?;
+        s.end()
+    }
+}
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use stylua_lib::{LineEndings, LuaVersion, OutputVerification, Range};
+
+use crate::error::PotassiumError;
+use crate::syntax::{self, split_header};
+
+/// Widest indent `indentWidth` may ask for.
+pub const MAX_INDENT_WIDTH: usize = 16;
+
+/// Narrowest `columnWidth` the config accepts.
+pub const MIN_COLUMN_WIDTH: usize = 40;
+
+// ─────────────────────────────────────────────────────────────
+// Settings
+// ─────────────────────────────────────────────────────────────
+// Names, values and defaults follow stylua.toml, so a project's
+// StyLua settings can be copied over as-is (camelCased keys).
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IndentType {
+    #[default]
+    Tabs,
+    Spaces,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuoteStyle {
+    #[default]
+    AutoPreferDouble,
+    AutoPreferSingle,
+    ForceDouble,
+    ForceSingle,
+}
+
+/// When calls with a single string or table argument keep their
+/// parentheses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CallParentheses {
+    #[default]
+    Always,
+    NoSingleString,
+    NoSingleTable,
+    None,
+    Input,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FormatSettings {
+    pub indent_type: IndentType,
+    pub indent_width: usize,
+    pub quote_style: QuoteStyle,
+    pub call_parentheses: CallParentheses,
+    /// Lines are wrapped to stay under this where possible.
+    pub column_width: usize,
+    /// Format the file on Save / Save As. Autosave never formats.
+    pub format_on_save: bool,
+}
+
+impl Default for FormatSettings {
+    fn default() -> Self {
+        Self {
+            indent_type: IndentType::Tabs,
+            indent_width: 4,
+            quote_style: QuoteStyle::AutoPreferDouble,
+            call_parentheses: CallParentheses::Always,
+            column_width: 120,
+            format_on_save: false,
+        }
+    }
+}
+
+impl FormatSettings {
+    pub fn validate(&self) -> Result<(), PotassiumError> {
+        if self.indent_width == 0 || self.indent_width > MAX_INDENT_WIDTH {
+            return Err(PotassiumError::Config {
+                message: format!("format.indentWidth must be between 1 and {}", MAX_INDENT_WIDTH),
+            });
+        }
+        if self.column_width < MIN_COLUMN_WIDTH {
+            return Err(PotassiumError::Config {
+                message: format!("format.columnWidth must be at least {}", MIN_COLUMN_WIDTH),
+            });
+        }
+        Ok(())
+    }
+
+    fn stylua(&self, line_endings: LineEndings) -> stylua_lib::Config {
+        stylua_lib::Config {
+            syntax: LuaVersion::Luau,
+            column_width: self.column_width,
+            line_endings,
+            indent_type: match self.indent_type {
+                IndentType::Tabs => stylua_lib::IndentType::Tabs,
+                IndentType::Spaces => stylua_lib::IndentType::Spaces,
+            },
+            indent_width: self.indent_width,
+            quote_style: match self.quote_style {
+                QuoteStyle::AutoPreferDouble => stylua_lib::QuoteStyle::AutoPreferDouble,
+                QuoteStyle::AutoPreferSingle => stylua_lib::QuoteStyle::AutoPreferSingle,
+                QuoteStyle::ForceDouble => stylua_lib::QuoteStyle::ForceDouble,
+                QuoteStyle::ForceSingle => stylua_lib::QuoteStyle::ForceSingle,
+            },
+            call_parentheses: match self.call_parentheses {
+                CallParentheses::Always => stylua_lib::CallParenType::Always,
+                CallParentheses::NoSingleString => stylua_lib::CallParenType::NoSingleString,
+                CallParentheses::NoSingleTable => stylua_lib::CallParenType::NoSingleTable,
+                CallParentheses::None => stylua_lib::CallParenType::None,
+                CallParentheses::Input => stylua_lib::CallParenType::Input,
+            },
+            ..Default::default()
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Formatting
+// ─────────────────────────────────────────────────────────────
+
+/// Lines to format, 1-based and inclusive, as in a Monaco selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineRange {
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// Byte offsets of the start of `range.start_line` and the end of
+/// `range.end_line` in `code`, clamped to the text.
+fn byte_range(code: &str, range: LineRange) -> (usize, usize) {
+    let starts: Vec<usize> = std::iter::once(0).chain(code.match_indices('\n').map(|(at, _)| at + 1)).collect();
+    let line_start = |line: usize| starts.get(line.saturating_sub(1)).copied().unwrap_or(code.len());
+    let start = line_start(range.start_line);
+    let end = line_start(range.end_line.max(range.start_line) + 1).saturating_sub(1).max(start);
+    (start, end)
+}
+
+/// Formats `code` as Luau. With `lines`, only the statements touching
+/// those lines are reformatted; the rest is returned untouched. An
+/// `OpiumwareScript` header is kept as it is and `OpiumwareSetting`
+/// packets are returned unchanged. Scripts that do not parse give the
+/// first syntax error.
+pub fn format(code: &str, settings: &FormatSettings, lines: Option<LineRange>) -> Result<String, PotassiumError> {
+    let Some((header, lua)) = split_header(code) else {
+        return Ok(code.to_string());
+    };
+    syntax::ensure_valid(code)?;
+
+    let range = lines.map(|lines| {
+        let (start, end) = byte_range(code, lines);
+        Range::from_values(Some(start.saturating_sub(header.len())), Some(end.saturating_sub(header.len())))
+    });
+    let line_endings = if lua.contains("\r\n") { LineEndings::Windows } else { LineEndings::Unix };
+    let formatted = stylua_lib::format_code(lua, settings.stylua(line_endings), range, OutputVerification::Full)
+        .map_err(|e| PotassiumError::Format { message: e.to_string() })?;
+    Ok(format!("{}{}", header, formatted))
+}
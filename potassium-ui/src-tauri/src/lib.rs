@@ -1,3 +1,25 @@
+pub mod autoexec;
+pub mod backend;
+pub mod bundle;
+pub mod catalog;
+pub mod codec;
+pub mod config;
+pub mod control;
+pub mod error;
+pub mod format;
+pub mod library;
+pub mod lint;
+pub mod mock;
+pub mod monitor;
+pub mod pool;
+pub mod reply;
+pub mod report;
+pub mod session;
+pub mod syntax;
+pub mod transport;
+pub mod watcher;
+pub mod workspace;
+
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
 fn greet(name: &str) -> String {
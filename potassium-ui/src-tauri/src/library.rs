@@ -0,0 +1,333 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::PotassiumError;
+use crate::workspace::{file_name, is_script};
+
+/// Sidecar in the library root holding tags and favorites.
+pub const LIBRARY_FILE: &str = ".potassium-library.json";
+
+/// Most hits `search` returns.
+pub const SEARCH_LIMIT: usize = 100;
+
+/// Bigger files are listed but their contents are not searched.
+const MAX_SEARCH_BYTES: u64 = 1024 * 1024;
+
+fn file_error(path: impl AsRef<Path>, e: impl ToString) -> PotassiumError {
+    PotassiumError::File { path: path.as_ref().display().to_string(), message: e.to_string() }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Entries
+// ─────────────────────────────────────────────────────────────
+
+/// One script in the library. `path` is relative to the library root
+/// with `/` separators and is what every library command takes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryScript {
+    pub path: String,
+    pub name: String,
+    pub tags: Vec<String>,
+    pub favorite: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryFolder {
+    pub name: String,
+    /// Relative to the library root; empty for the root itself.
+    pub path: String,
+    pub folders: Vec<LibraryFolder>,
+    pub scripts: Vec<LibraryScript>,
+}
+
+/// A script matching a search. `line` (1-based) and `preview` point at
+/// the first matching line when the match was in the contents.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub script: LibraryScript,
+    pub line: Option<usize>,
+    pub preview: Option<String>,
+}
+
+/// What the sidecar stores per script.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ScriptMeta {
+    tags: BTreeSet<String>,
+    favorite: bool,
+}
+
+impl ScriptMeta {
+    fn is_empty(&self) -> bool {
+        self.tags.is_empty() && !self.favorite
+    }
+}
+
+/// `rel` as a path under `root`. Absolute paths and `..` are rejected so
+/// commands cannot reach outside the library.
+fn resolve(root: &Path, rel: &str) -> Result<PathBuf, PotassiumError> {
+    let path = Path::new(rel);
+    if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(file_error(rel, "not a path inside the library"));
+    }
+    Ok(root.join(path))
+}
+
+fn relative(root: &Path, path: &Path) -> String {
+    let rel = path.strip_prefix(root).unwrap_or(path);
+    rel.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.'))
+}
+
+// ─────────────────────────────────────────────────────────────
+// Library
+// ─────────────────────────────────────────────────────────────
+
+#[derive(Debug)]
+struct Inner {
+    root: PathBuf,
+    meta: BTreeMap<String, ScriptMeta>,
+}
+
+impl Inner {
+    fn script(&self, path: &Path) -> LibraryScript {
+        let rel = relative(&self.root, path);
+        let meta = self.meta.get(&rel).cloned().unwrap_or_default();
+        LibraryScript {
+            name: path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+            path: rel,
+            tags: meta.tags.into_iter().collect(),
+            favorite: meta.favorite,
+        }
+    }
+
+    /// The folder at `dir`, with everything below it. Hidden entries and
+    /// symlinked folders are skipped.
+    fn folder(&self, dir: &Path) -> LibraryFolder {
+        let mut folders = Vec::new();
+        let mut scripts = Vec::new();
+        for entry in fs::read_dir(dir).into_iter().flatten().filter_map(Result::ok) {
+            let path = entry.path();
+            let Ok(kind) = entry.file_type() else {
+                continue;
+            };
+            if is_hidden(&path) {
+                continue;
+            }
+            if kind.is_dir() {
+                folders.push(self.folder(&path));
+            } else if path.is_file() && is_script(&path) {
+                scripts.push(self.script(&path));
+            }
+        }
+        folders.sort_by_key(|f| f.name.to_lowercase());
+        scripts.sort_by_key(|s| s.name.to_lowercase());
+        LibraryFolder {
+            name: dir.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+            path: relative(&self.root, dir),
+            folders,
+            scripts,
+        }
+    }
+
+    /// Every script path below the root.
+    fn files(&self) -> Vec<PathBuf> {
+        fn walk(dir: &Path, out: &mut Vec<PathBuf>) {
+            for entry in fs::read_dir(dir).into_iter().flatten().filter_map(Result::ok) {
+                let path = entry.path();
+                if is_hidden(&path) {
+                    continue;
+                }
+                match entry.file_type() {
+                    Ok(kind) if kind.is_dir() => walk(&path, out),
+                    _ if path.is_file() && is_script(&path) => out.push(path),
+                    _ => {}
+                }
+            }
+        }
+        let mut out = Vec::new();
+        walk(&self.root, &mut out);
+        out
+    }
+
+    fn existing(&self, rel: &str) -> Result<PathBuf, PotassiumError> {
+        let path = resolve(&self.root, rel)?;
+        if !path.is_file() {
+            return Err(file_error(rel, "no such script in the library"));
+        }
+        Ok(path)
+    }
+}
+
+fn load_meta(root: &Path) -> BTreeMap<String, ScriptMeta> {
+    fs::read_to_string(root.join(LIBRARY_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// A folder of saved scripts, browsed as a tree. Tags and favorites are
+/// kept in `LIBRARY_FILE` next to the scripts so the folder can be synced
+/// or shared as a whole. Managed as Tauri state.
+#[derive(Debug)]
+pub struct Library {
+    inner: Mutex<Inner>,
+}
+
+impl Library {
+    pub fn load(root: PathBuf) -> Self {
+        let meta = load_meta(&root);
+        Self { inner: Mutex::new(Inner { root, meta }) }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Writes the sidecar, dropping entries whose script is gone.
+    fn persist(&self, inner: &mut Inner) -> Result<(), PotassiumError> {
+        let root = inner.root.clone();
+        inner.meta.retain(|rel, meta| !meta.is_empty() && root.join(rel).is_file());
+        let path = root.join(LIBRARY_FILE);
+        fs::write(&path, serde_json::to_string_pretty(&inner.meta).unwrap_or_default()).map_err(|e| file_error(&path, e))
+    }
+
+    pub fn root(&self) -> PathBuf {
+        self.lock().root.clone()
+    }
+
+    /// Switches to another folder and reads its sidecar.
+    pub fn set_root(&self, root: PathBuf) {
+        let mut inner = self.lock();
+        if inner.root != root {
+            inner.meta = load_meta(&root);
+            inner.root = root;
+        }
+    }
+
+    /// The whole library. The root is created if missing.
+    pub fn tree(&self) -> Result<LibraryFolder, PotassiumError> {
+        let inner = self.lock();
+        fs::create_dir_all(&inner.root).map_err(|e| file_error(&inner.root, e))?;
+        Ok(inner.folder(&inner.root))
+    }
+
+    /// Absolute path of the script at `rel`.
+    pub fn path_of(&self, rel: &str) -> Result<PathBuf, PotassiumError> {
+        self.lock().existing(rel)
+    }
+
+    pub fn read(&self, rel: &str) -> Result<String, PotassiumError> {
+        let path = self.path_of(rel)?;
+        fs::read_to_string(&path).map_err(|e| file_error(&path, e))
+    }
+
+    /// Saves `content` as a new script `name` in `folder` (relative, empty
+    /// for the root), creating the folder. Existing scripts are not
+    /// overwritten.
+    pub fn add(&self, folder: &str, name: &str, content: &str) -> Result<LibraryScript, PotassiumError> {
+        let inner = self.lock();
+        let name = file_name(name)?;
+        if name.starts_with('.') {
+            return Err(file_error(&name, "not a valid file name"));
+        }
+        let dir = if folder.trim().is_empty() { inner.root.clone() } else { resolve(&inner.root, folder.trim())? };
+        fs::create_dir_all(&dir).map_err(|e| file_error(&dir, e))?;
+
+        let path = dir.join(&name);
+        if path.exists() {
+            return Err(file_error(&path, "a file with that name already exists"));
+        }
+        fs::write(&path, content).map_err(|e| file_error(&path, e))?;
+        Ok(inner.script(&path))
+    }
+
+    /// Replaces the tags of `rel`. Tags are trimmed; blanks and repeats
+    /// are dropped.
+    pub fn set_tags(&self, rel: &str, tags: &[String]) -> Result<LibraryScript, PotassiumError> {
+        let mut inner = self.lock();
+        let path = inner.existing(rel)?;
+        let key = relative(&inner.root, &path);
+        inner.meta.entry(key).or_default().tags =
+            tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()).map(str::to_string).collect();
+        self.persist(&mut inner)?;
+        Ok(inner.script(&path))
+    }
+
+    pub fn set_favorite(&self, rel: &str, favorite: bool) -> Result<LibraryScript, PotassiumError> {
+        let mut inner = self.lock();
+        let path = inner.existing(rel)?;
+        let key = relative(&inner.root, &path);
+        inner.meta.entry(key).or_default().favorite = favorite;
+        self.persist(&mut inner)?;
+        Ok(inner.script(&path))
+    }
+
+    /// Scripts matching every word of `query`, case-insensitively, in
+    /// their path, tags or contents. A word starting with `#` must be a
+    /// tag. Favorites come first, then name matches, then the rest by
+    /// path.
+    pub fn search(&self, query: &str) -> Vec<SearchHit> {
+        let inner = self.lock();
+        let mut tags = Vec::new();
+        let mut words = Vec::new();
+        for word in query.split_whitespace().map(str::to_lowercase) {
+            match word.strip_prefix('#') {
+                Some(tag) if !tag.is_empty() => tags.push(tag.to_string()),
+                Some(_) => {}
+                None => words.push(word),
+            }
+        }
+        if tags.is_empty() && words.is_empty() {
+            return Vec::new();
+        }
+
+        let mut hits = Vec::new();
+        for path in inner.files() {
+            let script = inner.script(&path);
+            let script_tags: Vec<String> = script.tags.iter().map(|t| t.to_lowercase()).collect();
+            if !tags.iter().all(|tag| script_tags.contains(tag)) {
+                continue;
+            }
+
+            let label = format!("{} {}", script.path.to_lowercase(), script_tags.join(" "));
+            let searchable = fs::metadata(&path).is_ok_and(|m| m.len() <= MAX_SEARCH_BYTES);
+            let text = if searchable { fs::read_to_string(&path).unwrap_or_default() } else { String::new() };
+            let lower = text.to_lowercase();
+            if !words.iter().all(|w| label.contains(w) || lower.contains(w)) {
+                continue;
+            }
+
+            let in_body = words.iter().find(|w| !label.contains(*w)).or(words.first());
+            let (line, preview) = match in_body {
+                Some(word) => text
+                    .lines()
+                    .enumerate()
+                    .find(|(_, l)| l.to_lowercase().contains(word))
+                    .map(|(i, l)| (Some(i + 1), Some(l.trim().to_string())))
+                    .unwrap_or((None, None)),
+                None => (None, None),
+            };
+            hits.push(SearchHit { script, line, preview });
+        }
+
+        hits.sort_by_key(|h| {
+            let name = h.script.name.to_lowercase();
+            (Reverse(h.script.favorite), !words.iter().any(|w| name.contains(w)), h.script.path.to_lowercase())
+        });
+        hits.truncate(SEARCH_LIMIT);
+        hits
+    }
+}
@@ -0,0 +1,367 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use full_moon::ast::{
+    Assignment, Block, FunctionBody, FunctionDeclaration, GenericFor, LocalAssignment, LocalFunction, NumericFor,
+    Parameter, Prefix, Repeat, Var,
+};
+use full_moon::tokenizer::TokenReference;
+use full_moon::visitors::Visitor;
+use full_moon::LuaVersion;
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::ApiCatalog;
+use crate::error::PotassiumError;
+use crate::syntax::{lua_source, Diagnostic, Severity, MAX_DIAGNOSTICS};
+
+/// Per-workspace rule settings, read from the workspace dir.
+pub const LINT_FILE: &str = ".potassium-lint.json";
+
+/// Globals every Roblox script can use without defining them: the Luau
+/// standard library plus the engine's globals and datatypes.
+pub const ROBLOX_GLOBALS: &[&str] = &[
+    // Luau
+    "_G", "_VERSION", "assert", "bit32", "buffer", "collectgarbage", "coroutine", "debug", "error", "gcinfo",
+    "getfenv", "getmetatable", "ipairs", "loadstring", "math", "newproxy", "next", "os", "pairs", "pcall", "print",
+    "rawequal", "rawget", "rawlen", "rawset", "require", "select", "setfenv", "setmetatable", "string", "table",
+    "tonumber", "tostring", "type", "typeof", "unpack", "utf8", "vector", "xpcall",
+    // Roblox globals
+    "DebuggerManager", "delay", "elapsedTime", "game", "Game", "plugin", "printidentity", "script", "settings",
+    "shared", "spawn", "stats", "task", "tick", "time", "UserSettings", "version", "wait", "warn", "workspace",
+    "Workspace", "ypcall",
+    // Roblox datatypes
+    "Axes", "BrickColor", "CatalogSearchParams", "CFrame", "Color3", "ColorSequence", "ColorSequenceKeypoint",
+    "Content", "DateTime", "DockWidgetPluginGuiInfo", "Enum", "Faces", "FloatCurveKey", "Font", "Instance",
+    "NumberRange", "NumberSequence", "NumberSequenceKeypoint", "OverlapParams", "Path2DControlPoint", "PathWaypoint",
+    "PhysicalProperties", "Random", "Ray", "RaycastParams", "Rect", "Region3", "Region3int16", "RotationCurveKey",
+    "SharedTable", "TweenInfo", "UDim", "UDim2", "Vector2", "Vector2int16", "Vector3", "Vector3int16",
+];
+
+fn lint_error(path: &Path, e: impl ToString) -> PotassiumError {
+    PotassiumError::File { path: path.display().to_string(), message: e.to_string() }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Rules
+// ─────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    /// A name that is neither a local, a builtin, in the API catalog, in
+    /// the allowlist nor assigned as a global anywhere in the script.
+    UndefinedGlobal,
+    /// A local declared while another local of the same name is in scope.
+    ShadowedLocal,
+    /// A local that is never read. Parameters and names starting with
+    /// `_` are exempt.
+    UnusedVariable,
+}
+
+impl Rule {
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::UndefinedGlobal => "undefined-global",
+            Rule::ShadowedLocal => "shadowed-local",
+            Rule::UnusedVariable => "unused-variable",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RuleLevel {
+    Off,
+    Warning,
+    Error,
+}
+
+impl RuleLevel {
+    fn severity(self) -> Option<Severity> {
+        match self {
+            RuleLevel::Off => None,
+            RuleLevel::Warning => Some(Severity::Warning),
+            RuleLevel::Error => Some(Severity::Error),
+        }
+    }
+}
+
+/// What `LINT_FILE` holds, e.g.
+/// `{ "rules": { "unused-variable": "off" }, "globals": ["Rayfield"] }`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LintConfig {
+    /// Level per rule. Rules not listed are warnings.
+    pub rules: BTreeMap<Rule, RuleLevel>,
+    /// Extra globals that are never reported as undefined.
+    pub globals: BTreeSet<String>,
+}
+
+impl LintConfig {
+    /// The config in `dir`, or the defaults if it has none.
+    pub fn load(dir: &Path) -> Result<Self, PotassiumError> {
+        let path = dir.join(LINT_FILE);
+        match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|e| lint_error(&path, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(lint_error(&path, e)),
+        }
+    }
+
+    pub fn level(&self, rule: Rule) -> RuleLevel {
+        self.rules.get(&rule).copied().unwrap_or(RuleLevel::Warning)
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Lint pass
+// ─────────────────────────────────────────────────────────────
+
+/// Lints `code` (headers handled as in `syntax::check`). Scripts that do
+/// not parse give no lint results; their syntax errors come from
+/// `syntax::check`. Names from `catalog` count as defined globals, as do
+/// the namespaces its entries live in.
+pub fn lint(code: &str, catalog: &ApiCatalog, config: &LintConfig) -> Vec<Diagnostic> {
+    let Some(source) = lua_source(code) else {
+        return Vec::new();
+    };
+    let result = full_moon::parse_fallible(&source, LuaVersion::luau());
+    if !result.errors().is_empty() {
+        return Vec::new();
+    }
+
+    let mut known: HashSet<String> = ROBLOX_GLOBALS.iter().map(|g| g.to_string()).collect();
+    known.extend(config.globals.iter().cloned());
+    for entry in &catalog.entries {
+        let names = std::iter::once(&entry.name).chain(&entry.aliases).chain(&entry.namespace);
+        known.extend(names.filter_map(|name| name.split('.').next()).map(str::to_string));
+    }
+
+    let mut pass = Pass::default();
+    pass.visit_ast(result.ast());
+    while !pass.scopes.is_empty() {
+        pass.pop_scope();
+    }
+
+    let mut diagnostics = Vec::new();
+    let mut report = |rule: Rule, token: &TokenReference, message: String| {
+        if let Some(severity) = config.level(rule).severity() {
+            diagnostics.push(diagnostic(token, message, severity, rule));
+        }
+    };
+    for (token, message) in &pass.shadowed {
+        report(Rule::ShadowedLocal, token, message.clone());
+    }
+    for token in &pass.unused {
+        report(Rule::UnusedVariable, token, format!("`{}` is never used", name_of(token)));
+    }
+    for token in &pass.global_reads {
+        let name = name_of(token);
+        if !known.contains(&name) && !pass.global_writes.contains(&name) {
+            report(Rule::UndefinedGlobal, token, format!("`{}` is not defined", name));
+        }
+    }
+
+    diagnostics.sort_by_key(|d| (d.line, d.column));
+    diagnostics.truncate(MAX_DIAGNOSTICS);
+    diagnostics
+}
+
+fn name_of(token: &TokenReference) -> String {
+    token.token().to_string()
+}
+
+fn diagnostic(token: &TokenReference, message: String, severity: Severity, rule: Rule) -> Diagnostic {
+    let (start, end) = (token.start_position(), token.end_position());
+    Diagnostic {
+        line: start.line(),
+        column: start.character(),
+        end_line: end.line(),
+        end_column: end.character(),
+        message,
+        severity,
+        rule: Some(rule.name().to_string()),
+    }
+}
+
+/// Identity of an AST node for the duration of one pass.
+fn key<T>(node: &T) -> usize {
+    node as *const T as usize
+}
+
+#[derive(Debug)]
+struct Binding {
+    name: String,
+    token: TokenReference,
+    used: bool,
+    /// Parameters and `self` are never reported as unused.
+    param: bool,
+}
+
+#[derive(Debug, Default)]
+struct Scope {
+    block: usize,
+    bindings: Vec<Binding>,
+}
+
+/// Walks the AST keeping a stack of scopes, one per block. Names that
+/// belong to a block but are declared before it (parameters, loop
+/// variables) wait in `pending` until the block is entered; a `repeat`
+/// block stays open until its `until` condition has been visited.
+#[derive(Debug, Default)]
+struct Pass {
+    scopes: Vec<Scope>,
+    pending: HashMap<usize, Vec<Binding>>,
+    held_open: HashSet<usize>,
+    /// Names that are assignment targets, so `visit_var` skips them.
+    writes: HashSet<usize>,
+    global_reads: Vec<TokenReference>,
+    global_writes: HashSet<String>,
+    shadowed: Vec<(TokenReference, String)>,
+    unused: Vec<TokenReference>,
+}
+
+impl Pass {
+    fn lookup(&mut self, name: &str) -> Option<&mut Binding> {
+        self.scopes.iter_mut().rev().flat_map(|s| s.bindings.iter_mut().rev()).find(|b| b.name == name)
+    }
+
+    fn binding(&mut self, token: &TokenReference, param: bool) -> Binding {
+        let name = name_of(token);
+        if name != "_" {
+            if let Some(outer) = self.lookup(&name) {
+                let line = outer.token.start_position().line();
+                self.shadowed.push((token.clone(), format!("`{}` shadows a local declared on line {}", name, line)));
+            }
+        }
+        Binding { name, token: token.clone(), used: false, param }
+    }
+
+    fn declare(&mut self, token: &TokenReference) {
+        let binding = self.binding(token, false);
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.bindings.push(binding);
+        }
+    }
+
+    /// Declares `token` in the scope of `block`, once it is entered.
+    fn declare_in(&mut self, block: &Block, token: &TokenReference, param: bool) {
+        let binding = self.binding(token, param);
+        self.pending.entry(key(block)).or_default().push(binding);
+    }
+
+    fn read(&mut self, token: &TokenReference) {
+        match self.lookup(&name_of(token)) {
+            Some(binding) => binding.used = true,
+            None => self.global_reads.push(token.clone()),
+        }
+    }
+
+    /// `name = ...`: a write to the local if there is one, otherwise a
+    /// global definition.
+    fn write(&mut self, token: &TokenReference) {
+        let name = name_of(token);
+        if self.lookup(&name).is_none() {
+            self.global_writes.insert(name);
+        }
+    }
+
+    fn pop_scope(&mut self) {
+        if let Some(scope) = self.scopes.pop() {
+            let unused = scope.bindings.into_iter().filter(|b| !b.used && !b.param && !b.name.starts_with('_'));
+            self.unused.extend(unused.map(|b| b.token));
+        }
+    }
+}
+
+impl Visitor for Pass {
+    fn visit_block(&mut self, block: &Block) {
+        let bindings = self.pending.remove(&key(block)).unwrap_or_default();
+        self.scopes.push(Scope { block: key(block), bindings });
+    }
+
+    fn visit_block_end(&mut self, block: &Block) {
+        if !self.held_open.contains(&key(block)) {
+            self.pop_scope();
+        }
+    }
+
+    fn visit_repeat(&mut self, node: &Repeat) {
+        self.held_open.insert(key(node.block()));
+    }
+
+    fn visit_repeat_end(&mut self, node: &Repeat) {
+        self.held_open.remove(&key(node.block()));
+        if self.scopes.last().is_some_and(|s| s.block == key(node.block())) {
+            self.pop_scope();
+        }
+    }
+
+    fn visit_local_assignment_end(&mut self, node: &LocalAssignment) {
+        for name in node.names() {
+            self.declare(name);
+        }
+    }
+
+    fn visit_local_function(&mut self, node: &LocalFunction) {
+        self.declare(node.name());
+    }
+
+    fn visit_function_declaration(&mut self, node: &FunctionDeclaration) {
+        let name = node.name();
+        if let Some(first) = name.names().iter().next() {
+            if name.names().len() == 1 && name.method_name().is_none() {
+                self.write(first);
+            } else {
+                self.read(first);
+            }
+        }
+        // Methods get an implicit `self`, which may shadow an outer one.
+        if let Some(method) = name.method_name() {
+            let this = Binding { name: "self".into(), token: method.clone(), used: false, param: true };
+            self.pending.entry(key(node.body().block())).or_default().push(this);
+        }
+    }
+
+    fn visit_function_body(&mut self, node: &FunctionBody) {
+        for parameter in node.parameters() {
+            if let Parameter::Name(name) = parameter {
+                self.declare_in(node.block(), name, true);
+            }
+        }
+    }
+
+    fn visit_numeric_for(&mut self, node: &NumericFor) {
+        self.declare_in(node.block(), node.index_variable(), false);
+    }
+
+    fn visit_generic_for(&mut self, node: &GenericFor) {
+        for name in node.names() {
+            self.declare_in(node.block(), name, false);
+        }
+    }
+
+    fn visit_assignment(&mut self, node: &Assignment) {
+        for var in node.variables() {
+            if let Var::Name(name) = var {
+                self.writes.insert(key(name));
+                self.write(name);
+            }
+        }
+    }
+
+    fn visit_var(&mut self, var: &Var) {
+        if let Var::Name(name) = var {
+            if !self.writes.contains(&key(name)) {
+                self.read(name);
+            }
+        }
+    }
+
+    fn visit_prefix(&mut self, prefix: &Prefix) {
+        if let Prefix::Name(name) = prefix {
+            self.read(name);
+        }
+    }
+}
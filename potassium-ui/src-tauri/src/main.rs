@@ -1,133 +1,928 @@
-use std::io::Write;
-use std::net::TcpStream;
-use std::time::Duration;
-use std::error::Error;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 
-use flate2::write::ZlibEncoder;
-use flate2::Compression;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use notify::RecursiveMode;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, State, Window};
 
-use tauri::Window;
+use potassium_ui_lib::autoexec::{self, AutoexecFile, AutoexecLog, AutoexecRun, AUTOEXEC_RAN};
+use potassium_ui_lib::backend::{BackendInfo, BackendRegistry, ExecutorBackend, Opiumware};
+use potassium_ui_lib::bundle::{self, Bundle, Script};
+use potassium_ui_lib::catalog::{ApiCatalog, CATALOG_FILES};
+use potassium_ui_lib::config::{Config, ConfigStore};
+use potassium_ui_lib::control::{
+    self, ControlHandler, ControlServer, ControlStatus, EXECUTION_RESULT, TOKEN_FILE,
+};
+use potassium_ui_lib::error::PotassiumError;
+use potassium_ui_lib::format::{self, LineRange};
+use potassium_ui_lib::library::{Library, LibraryFolder, LibraryScript, SearchHit};
+use potassium_ui_lib::lint::{self, LintConfig};
+use potassium_ui_lib::monitor::{InstanceMonitor, InstanceStatus};
+use potassium_ui_lib::pool::{ConnectionPool, ConnectionStatus, CONNECTION_STATE};
+use potassium_ui_lib::reply::{ConsoleEvent, CONSOLE_OUTPUT};
+use potassium_ui_lib::report::ExecutionReport;
+use potassium_ui_lib::session::{AttachedPort, Session, SessionInfo};
+use potassium_ui_lib::syntax::{self, Diagnostic};
+use potassium_ui_lib::transport::{Client, Target, LEGACY_PROBE_SCRIPT};
+use potassium_ui_lib::watcher::{ScriptWatcher, FILE_CHANGED};
+use potassium_ui_lib::workspace::{TabContent, TabInfo, Workspace, WorkspaceInfo, WORKSPACE_FILE};
 
 // ─────────────────────────────────────────────────────────────
-// Compression helper
+// Persistent connections
+// With persistentConnections on, attaching a port also opens a
+// pooled connection to it and executes go over that connection.
 // ─────────────────────────────────────────────────────────────
-fn compress_data(data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
-    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
-    encoder.write_all(data)?;
-    let compressed_data = encoder.finish()?;
-    Ok(compressed_data)
+fn client_for(config: &Config, pool: &Arc<ConnectionPool>) -> Client {
+    if config.persistent_connections {
+        config.client().with_pool(pool.clone())
+    } else {
+        config.client()
+    }
+}
+
+fn attach_port(
+    app: &AppHandle,
+    config: &Config,
+    session: &Session,
+    pool: &ConnectionPool,
+    port: &str,
+) -> AttachedPort {
+    let (attached, newly_attached) = session.attach(port);
+    if config.persistent_connections {
+        pool.open(&config.client(), port);
+    }
+    if newly_attached {
+        spawn_autoexec(app, config, port);
+    }
+    attached
+}
+
+fn detach_port(session: &Session, pool: &ConnectionPool, port: &str) -> bool {
+    pool.close(port);
+    session.detach(port)
+}
+
+// First port the backend finds a listener on.
+async fn discover_first(backend: &dyn ExecutorBackend, client: &Client) -> Result<String, PotassiumError> {
+    backend.discover(client).await.into_iter().next().ok_or(PotassiumError::NoInstanceFound)
+}
+
+// Pushes each port's reply to the console panel and the report to
+// control API clients.
+fn emit_report(app: &AppHandle, report: &ExecutionReport) {
+    app.state::<ControlServer>().publish(EXECUTION_RESULT, report);
+    for port in &report.ports {
+        if let Some(reply) = &port.reply {
+            let _ = app.emit(CONSOLE_OUTPUT, &ConsoleEvent::new(&port.port, &reply.text));
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Bundle line map
+// The last script executed, so ":<line>:" positions in listener
+// output (replies, and later output on pooled connections) also
+// name the module file and line they came from.
+// ─────────────────────────────────────────────────────────────
+#[derive(Default)]
+struct LastBundle(Mutex<Option<Bundle>>);
+
+impl LastBundle {
+    fn set(&self, bundle: &Bundle) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = bundle.is_bundled().then(|| bundle.clone());
+    }
+
+    fn annotate(&self, text: &str) -> String {
+        match &*self.0.lock().unwrap_or_else(|e| e.into_inner()) {
+            Some(bundle) => bundle.annotate(text),
+            None => text.to_string(),
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Shared execute path (every execute: commands, control API,
+// autoexec). Bundles the script's requires and adds the header,
+// checks the syntax (if blockOnSyntaxError) and the target
+// against the session, sends, maps reply lines back to module
+// files, and records activity.
+// ─────────────────────────────────────────────────────────────
+async fn execute_in_session(
+    config: &ConfigStore,
+    registry: &BackendRegistry,
+    session: &Session,
+    pool: &Arc<ConnectionPool>,
+    last_bundle: &LastBundle,
+    script: Script<'_>,
+    port: &str,
+) -> Result<ExecutionReport, PotassiumError> {
+    let target = Target::parse(port);
+    let config = config.get();
+    let backend = registry.get(&config.backend)?;
+    let bundle = script.load(config.block_on_syntax_error)?;
+
+    let warnings = session.check_target(&target, config.require_attach)?;
+    last_bundle.set(&bundle);
+    let mut report = backend.execute(&client_for(&config, pool), &bundle.code, &target).await?;
+    bundle.annotate_replies(&mut report);
+    report.warnings.splice(0..0, warnings);
+    session.record(&report);
+    Ok(report)
 }
 
 // ─────────────────────────────────────────────────────────────
-// Port list
+// Shared probe path (probe + the JS API's "NULL" shim)
+// Connects without sending anything. A successful probe of a
+// single port counts as attaching to it.
 // ─────────────────────────────────────────────────────────────
-const PORTS: &[&str] = &["8392", "8393", "8394", "8395", "8396", "8397"];
+async fn probe_in_session(
+    app: &AppHandle,
+    config: &ConfigStore,
+    registry: &BackendRegistry,
+    session: &Session,
+    pool: &ConnectionPool,
+    port: &str,
+) -> Result<ExecutionReport, PotassiumError> {
+    let target = Target::parse(port);
+    let config = config.get();
+    let report = registry.get(&config.backend)?.probe_target(&config.client(), &target).await?;
+    if let (Target::Port(p), true) = (&target, report.is_success()) {
+        attach_port(app, &config, session, pool, p);
+    }
+    Ok(report)
+}
+
+// ─────────────────────────────────────────────────────────────
+// COMMAND: probe
+// Tests the given port (or ALL configured ports) without sending
+// a script. Returns an ExecutionReport with probe = true.
+// ─────────────────────────────────────────────────────────────
+#[tauri::command]
+async fn probe(
+    app: AppHandle,
+    config: State<'_, ConfigStore>,
+    registry: State<'_, BackendRegistry>,
+    session: State<'_, Session>,
+    pool: State<'_, Arc<ConnectionPool>>,
+    port: String,
+) -> Result<ExecutionReport, PotassiumError> {
+    probe_in_session(&app, &config, &registry, &session, &pool, &port).await
+}
 
 // ─────────────────────────────────────────────────────────────
 // COMMAND: OpiumwareAttach
-// Scans all ports, returns the first reachable one.
+// Scans the configured ports, returns the first reachable one.
 // ─────────────────────────────────────────────────────────────
 #[tauri::command]
 #[allow(non_snake_case)]
-async fn OpiumwareAttach() -> String {
-    for port in PORTS {
-        let addr = format!("127.0.0.1:{}", port);
-        match TcpStream::connect_timeout(&addr.parse().unwrap(), Duration::from_millis(400)) {
-            Ok(_) => {
-                println!("[Potassium] Attached on port {}", port);
-                return format!("Successfully attached on port {}", port);
-            }
-            Err(e) => println!("[Potassium] Port {} unavailable: {}", port, e),
-        }
-    }
-    "Failed to attach: no Opiumware instance found on ports 8392-8397".to_string()
+async fn OpiumwareAttach(
+    app: AppHandle,
+    config: State<'_, ConfigStore>,
+    registry: State<'_, BackendRegistry>,
+    session: State<'_, Session>,
+    pool: State<'_, Arc<ConnectionPool>>,
+) -> Result<String, PotassiumError> {
+    let config = config.get();
+    let backend = registry.get(&config.backend)?;
+    let port = discover_first(backend.as_ref(), &config.client()).await?;
+    attach_port(&app, &config, &session, &pool, &port);
+    Ok(port)
 }
 
 // ─────────────────────────────────────────────────────────────
 // COMMAND: OpiumwareExecution
-// Connects to the given port (or ALL ports) and sends the
-// zlib-compressed script. Matches your exact API spec.
+// Connects to the given port (or ALL configured ports) and sends
+// the editor code, bundled relative to the tab's file (`id`) and
+// with the header added. Returns a per-port ExecutionReport.
 // ─────────────────────────────────────────────────────────────
 #[tauri::command]
 #[allow(non_snake_case)]
-async fn OpiumwareExecution(code: String, port: String) -> String {
-    let ports_to_check: Vec<String> = match port.as_str() {
-        "ALL" => PORTS.iter().map(|s| s.to_string()).collect(),
-        _     => vec![port],
+#[allow(clippy::too_many_arguments)]
+async fn OpiumwareExecution(
+    app: AppHandle,
+    config: State<'_, ConfigStore>,
+    registry: State<'_, BackendRegistry>,
+    session: State<'_, Session>,
+    pool: State<'_, Arc<ConnectionPool>>,
+    workspace: State<'_, Workspace>,
+    code: String,
+    port: String,
+    id: Option<String>,
+) -> Result<ExecutionReport, PotassiumError> {
+    let path = id.and_then(|id| workspace.path_of(&id));
+    let script = Script::Source { code: &code, path: path.as_deref(), root: &workspace.dir() };
+    let report =
+        execute_in_session(&config, &registry, &session, &pool, &app.state::<LastBundle>(), script, &port).await?;
+    emit_report(&app, &report);
+    Ok(report)
+}
+
+// ─────────────────────────────────────────────────────────────
+// COMMAND: validate_script
+// Luau syntax check for the editor. Diagnostics are 1-based
+// like Monaco markers; an empty list means the script parses.
+// ─────────────────────────────────────────────────────────────
+#[tauri::command]
+async fn validate_script(code: String) -> Result<Vec<Diagnostic>, PotassiumError> {
+    Ok(syntax::check(&code))
+}
+
+// ─────────────────────────────────────────────────────────────
+// COMMAND: bundle_script
+// The listener takes one flat chunk, so every execute inlines
+// require("./x") modules behind a module table before sending.
+// Paths are relative to the tab's file (`id`), or the workspace
+// dir for code without one. Returns exactly what Execute sends,
+// for the bundle preview.
+// ─────────────────────────────────────────────────────────────
+#[tauri::command]
+async fn bundle_script(
+    workspace: State<'_, Workspace>,
+    code: String,
+    id: Option<String>,
+) -> Result<Bundle, PotassiumError> {
+    let path = id.and_then(|id| workspace.path_of(&id));
+    bundle::prepare(&code, path.as_deref(), &workspace.dir())
+}
+
+// ─────────────────────────────────────────────────────────────
+// COMMAND: format_script
+// Formats the buffer with the StyLua-style settings in the
+// backend config ("format"). With `lines` (1-based, inclusive)
+// only that selection is reformatted. Returns the whole text.
+// ─────────────────────────────────────────────────────────────
+#[tauri::command]
+async fn format_script(
+    config: State<'_, ConfigStore>,
+    code: String,
+    lines: Option<LineRange>,
+) -> Result<String, PotassiumError> {
+    format::format(&code, &config.get().format, lines)
+}
+
+// ─────────────────────────────────────────────────────────────
+// COMMAND: get_api_catalog
+// The API catalog behind completions and hover docs. An
+// api-catalog.json / .toml in the app config dir replaces the
+// built-in src/api-catalog.json; errors in it are
+// returned rather than silently ignored.
+// ─────────────────────────────────────────────────────────────
+fn api_catalog(app: &AppHandle) -> Result<ApiCatalog, PotassiumError> {
+    let Ok(config_dir) = app.path().app_config_dir() else {
+        return Ok(ApiCatalog::builtin());
     };
+    match CATALOG_FILES.iter().map(|name| config_dir.join(name)).find(|path| path.is_file()) {
+        Some(path) => ApiCatalog::load(&path),
+        None => Ok(ApiCatalog::builtin()),
+    }
+}
+
+#[tauri::command]
+async fn get_api_catalog(app: AppHandle) -> Result<ApiCatalog, PotassiumError> {
+    api_catalog(&app)
+}
+
+// ─────────────────────────────────────────────────────────────
+// COMMAND: lint_script
+// Undefined globals, shadowed locals and unused variables, for
+// the problems list. Rule levels and extra globals come from
+// .potassium-lint.json in the workspace dir; names in the API
+// catalog count as defined. Run on demand and after each save.
+// ─────────────────────────────────────────────────────────────
+#[tauri::command]
+async fn lint_script(app: AppHandle, code: String) -> Result<Vec<Diagnostic>, PotassiumError> {
+    let config = LintConfig::load(&app.state::<Workspace>().dir())?;
+    // A broken user catalog is reported by get_api_catalog; lint
+    // against the built-in one rather than failing here too.
+    let catalog = api_catalog(&app).unwrap_or_else(|_| ApiCatalog::builtin());
+    Ok(lint::lint(&code, &catalog, &config))
+}
 
-    // Inner send helper — mirrors your send_bytes exactly
-    fn send_bytes(stream: &mut TcpStream, message: &str) -> Result<(), String> {
-        let plaintext  = message.as_bytes();
-        let compressed = compress_data(plaintext).map_err(|e| e.to_string())?;
-        stream.write_all(&compressed).map_err(|e| e.to_string())?;
-        println!("Script sent ({} bytes)", compressed.len());
-        Ok(())
+// ─────────────────────────────────────────────────────────────
+// COMMAND: OpiumwareDetach
+// ─────────────────────────────────────────────────────────────
+#[tauri::command]
+#[allow(non_snake_case)]
+async fn OpiumwareDetach(
+    session: State<'_, Session>,
+    pool: State<'_, Arc<ConnectionPool>>,
+    port: String,
+) -> Result<String, PotassiumError> {
+    detach_port(&session, &pool, &port);
+    Ok(format!("Detached from port {}", port))
+}
+
+// ─────────────────────────────────────────────────────────────
+// COMMAND: get_session / attach / detach
+// Backend-owned attach state: which ports are attached, when,
+// and when they last ran a script.
+// ─────────────────────────────────────────────────────────────
+#[tauri::command]
+async fn get_session(session: State<'_, Session>) -> Result<SessionInfo, PotassiumError> {
+    Ok(session.snapshot())
+}
+
+#[tauri::command]
+async fn attach(
+    app: AppHandle,
+    config: State<'_, ConfigStore>,
+    registry: State<'_, BackendRegistry>,
+    session: State<'_, Session>,
+    pool: State<'_, Arc<ConnectionPool>>,
+    port: String,
+) -> Result<AttachedPort, PotassiumError> {
+    let config = config.get();
+    registry.get(&config.backend)?.probe(&config.client(), &port).await?;
+    Ok(attach_port(&app, &config, &session, &pool, &port))
+}
+
+#[tauri::command]
+async fn detach(
+    session: State<'_, Session>,
+    pool: State<'_, Arc<ConnectionPool>>,
+    port: String,
+) -> Result<SessionInfo, PotassiumError> {
+    detach_port(&session, &pool, &port);
+    Ok(session.snapshot())
+}
+
+// ─────────────────────────────────────────────────────────────
+// COMMAND: get_connections
+// State of each pooled connection. Changes are pushed as
+// "connection-state" events.
+// ─────────────────────────────────────────────────────────────
+#[tauri::command]
+async fn get_connections(pool: State<'_, Arc<ConnectionPool>>) -> Result<Vec<ConnectionStatus>, PotassiumError> {
+    Ok(pool.states())
+}
+
+// ─────────────────────────────────────────────────────────────
+// COMMAND: check_port
+// ─────────────────────────────────────────────────────────────
+#[tauri::command]
+async fn check_port(
+    config: State<'_, ConfigStore>,
+    registry: State<'_, BackendRegistry>,
+    port: String,
+) -> Result<bool, PotassiumError> {
+    let config = config.get();
+    match registry.get(&config.backend)?.probe(&config.client(), &port).await {
+        Ok(()) => Ok(true),
+        Err(e @ (PotassiumError::InvalidPort { .. } | PotassiumError::AddrParse { .. })) => Err(e),
+        Err(_) => Ok(false),
     }
+}
 
-    let mut any_success   = false;
-    let mut last_error    = String::new();
-    let mut success_ports: Vec<String> = Vec::new();
-
-    for p in &ports_to_check {
-        let server_address = format!("127.0.0.1:{}", p);
-        let addr: std::net::SocketAddr = server_address.parse().unwrap();
-        match TcpStream::connect_timeout(&addr, Duration::from_millis(800)) {
-            Ok(mut stream) => {
-                println!("Successfully connected to Opiumware on port: {}", p);
-                if code != "NULL" {
-                    match send_bytes(&mut stream, &code) {
-                        Ok(_) => {
-                            any_success = true;
-                            success_ports.push(p.clone());
-                        }
-                        Err(e) => {
-                            last_error = format!("Error sending script: {}", e);
-                            eprintln!("[Potassium] {}", last_error);
-                        }
-                    }
-                } else {
-                    // NULL = connection test / attach probe
-                    any_success = true;
-                    success_ports.push(p.clone());
+// ─────────────────────────────────────────────────────────────
+// COMMAND: get_instances
+// Last state seen by the background monitor. Changes are pushed
+// as "instance-up" / "instance-down" events.
+// ─────────────────────────────────────────────────────────────
+#[tauri::command]
+async fn get_instances(monitor: State<'_, Arc<InstanceMonitor>>) -> Result<Vec<InstanceStatus>, PotassiumError> {
+    Ok(monitor.snapshot())
+}
+
+// ─────────────────────────────────────────────────────────────
+// COMMAND: get_config / set_config
+// Host, port list/range and port labels. set_config validates,
+// persists to the app config dir and applies immediately.
+// ─────────────────────────────────────────────────────────────
+#[tauri::command]
+async fn get_config(config: State<'_, ConfigStore>) -> Result<Config, PotassiumError> {
+    Ok(config.get())
+}
+
+#[tauri::command]
+async fn set_config(
+    app: AppHandle,
+    config: State<'_, ConfigStore>,
+    registry: State<'_, BackendRegistry>,
+    session: State<'_, Session>,
+    pool: State<'_, Arc<ConnectionPool>>,
+    new_config: Config,
+) -> Result<Config, PotassiumError> {
+    registry.get(&new_config.backend)?;
+    let applied = config.set(new_config)?;
+    // Host or port changes invalidate open connections; reopen the
+    // attached ports if persistent mode is (still) on.
+    pool.close_all();
+    if applied.persistent_connections {
+        for attached in session.snapshot().ports {
+            pool.open(&applied.client(), &attached.port);
+        }
+    }
+    app.state::<Workspace>().set_dir(workspace_dir(&app, &applied));
+    app.state::<Library>().set_root(library_dir(&app, &applied));
+    watch_workspace(&app);
+    restart_control(&app).await;
+    Ok(applied)
+}
+
+// ─────────────────────────────────────────────────────────────
+// CONTROL API
+// Localhost HTTP + WebSocket server for editor plugins, on/off
+// via the config's "control" section. Requests go through the
+// same session and execute path as the commands above, and the
+// WebSocket carries the events the frontend gets.
+// ─────────────────────────────────────────────────────────────
+struct AppControl {
+    app: AppHandle,
+}
+
+impl ControlHandler for AppControl {
+    fn status(&self) -> BoxFuture<'static, Result<ControlStatus, PotassiumError>> {
+        let app = self.app.clone();
+        async move {
+            Ok(ControlStatus {
+                backend: app.state::<ConfigStore>().get().backend,
+                session: app.state::<Session>().snapshot(),
+                instances: app.state::<Arc<InstanceMonitor>>().snapshot(),
+                connections: app.state::<Arc<ConnectionPool>>().states(),
+            })
+        }
+        .boxed()
+    }
+
+    fn attach(&self, port: Option<String>) -> BoxFuture<'static, Result<AttachedPort, PotassiumError>> {
+        let app = self.app.clone();
+        async move {
+            let config = app.state::<ConfigStore>().get();
+            let backend = app.state::<BackendRegistry>().get(&config.backend)?;
+            let port = match port {
+                Some(port) => {
+                    backend.probe(&config.client(), &port).await?;
+                    port
                 }
-                drop(stream);
-            }
-            Err(e) => {
-                last_error = format!("Failed to connect to port {}: {}", p, e);
-                println!("[Potassium] {}", last_error);
-            }
+                None => discover_first(backend.as_ref(), &config.client()).await?,
+            };
+            Ok(attach_port(&app, &config, &app.state::<Session>(), &app.state::<Arc<ConnectionPool>>(), &port))
         }
+        .boxed()
     }
 
-    if any_success {
-        if success_ports.len() == 1 {
-            format!("Successfully connected to Opiumware on port: {}", success_ports[0])
-        } else {
-            format!("Successfully executed on ports: {}", success_ports.join(", "))
+    fn execute(
+        &self,
+        code: String,
+        port: Option<String>,
+        raw: bool,
+    ) -> BoxFuture<'static, Result<ExecutionReport, PotassiumError>> {
+        let app = self.app.clone();
+        async move {
+            let session = app.state::<Session>();
+            let port = match port {
+                Some(port) => port,
+                None => match session.snapshot().ports.into_iter().next() {
+                    Some(attached) => attached.port,
+                    None => return Err(PotassiumError::NoInstanceFound),
+                },
+            };
+            let root = app.state::<Workspace>().dir();
+            let script = if raw { Script::Raw(&code) } else { Script::Source { code: &code, path: None, root: &root } };
+            let report = execute_in_session(
+                &app.state::<ConfigStore>(),
+                &app.state::<BackendRegistry>(),
+                &session,
+                &app.state::<Arc<ConnectionPool>>(),
+                &app.state::<LastBundle>(),
+                script,
+                &port,
+            )
+            .await?;
+            emit_report(&app, &report);
+            Ok(report)
         }
-    } else {
-        format!("Failed to connect on all ports. Last error: {}", last_error)
+        .boxed()
     }
 }
 
+fn token_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path().app_config_dir().map(|dir| dir.join(TOKEN_FILE)).map_err(|e| e.to_string())
+}
+
+// Starts, restarts or stops the control API to match the config.
+async fn restart_control(app: &AppHandle) {
+    let settings = app.state::<ConfigStore>().get().control;
+    let server = app.state::<ControlServer>();
+    if !settings.enabled {
+        server.stop().await;
+        return;
+    }
+    let token = match token_path(app).and_then(|p| control::load_or_create_token(&p).map_err(|e| e.to_string())) {
+        Ok(token) => token,
+        Err(e) => {
+            eprintln!("[Potassium] Control API token unavailable: {}", e);
+            server.stop().await;
+            return;
+        }
+    };
+    let handler = Arc::new(AppControl { app: app.clone() });
+    if let Err(e) = server.start(settings.port, token, settings.allowed_origins.clone(), handler).await {
+        eprintln!("[Potassium] Control API failed to start on port {}: {}", settings.port, e);
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ControlInfo {
+    enabled: bool,
+    /// "127.0.0.1:port" while the server is running.
+    address: Option<String>,
+    token: String,
+}
+
 // ─────────────────────────────────────────────────────────────
-// COMMAND: OpiumwareDetach
+// COMMAND: get_control_info / reset_control_token
+// Address and token for pasting into an editor plugin.
+// Resetting the token restarts the API, dropping connected
+// clients.
 // ─────────────────────────────────────────────────────────────
 #[tauri::command]
-#[allow(non_snake_case)]
-async fn OpiumwareDetach(port: String) -> String {
-    println!("[Potassium] Detached from port {}", port);
-    format!("Detached from port {}", port)
+async fn get_control_info(app: AppHandle) -> Result<ControlInfo, String> {
+    let token = control::load_or_create_token(&token_path(&app)?).map_err(|e| e.to_string())?;
+    Ok(ControlInfo {
+        enabled: app.state::<ConfigStore>().get().control.enabled,
+        address: app.state::<ControlServer>().address().map(|a| a.to_string()),
+        token,
+    })
+}
+
+#[tauri::command]
+async fn reset_control_token(app: AppHandle) -> Result<ControlInfo, String> {
+    control::reset_token(&token_path(&app)?).map_err(|e| e.to_string())?;
+    restart_control(&app).await;
+    get_control_info(app).await
 }
 
 // ─────────────────────────────────────────────────────────────
-// COMMAND: check_port
+// COMMAND: list_backends / set_backend
+// Registered executor protocols, listed in the API dropdown.
+// set_backend switches the config's backend and persists it.
+// ─────────────────────────────────────────────────────────────
+#[tauri::command]
+async fn list_backends(registry: State<'_, BackendRegistry>) -> Result<Vec<BackendInfo>, PotassiumError> {
+    Ok(registry.list())
+}
+
+#[tauri::command]
+async fn set_backend(
+    config: State<'_, ConfigStore>,
+    registry: State<'_, BackendRegistry>,
+    id: String,
+) -> Result<Config, PotassiumError> {
+    registry.get(&id)?;
+    let mut updated = config.get();
+    updated.backend = id;
+    config.set(updated)
+}
+
+// ─────────────────────────────────────────────────────────────
+// WORKSPACE
+// Every tab is a real file. New tabs are created in the
+// workspace dir (config "workspaceDir", default
+// Documents/Potassium); opened files stay where they are. The
+// tab list lives in workspace.json in the app config dir.
 // ─────────────────────────────────────────────────────────────
+fn workspace_dir(app: &AppHandle, config: &Config) -> PathBuf {
+    config
+        .workspace_dir
+        .clone()
+        .or_else(|| app.path().document_dir().ok().map(|dir| dir.join("Potassium")))
+        .unwrap_or_else(|| app.path().app_data_dir().unwrap_or_default().join("workspace"))
+}
+
+// Points the file watcher at the workspace dir and the folders of
+// open files. Call after anything that changes which files are open.
+fn watch_workspace(app: &AppHandle) {
+    let Some(watcher) = app.try_state::<ScriptWatcher>() else {
+        return;
+    };
+    let workspace = app.state::<Workspace>();
+    let dirs: BTreeMap<PathBuf, RecursiveMode> = std::iter::once(workspace.dir())
+        .chain(workspace.paths().iter().filter_map(|p| p.parent().map(PathBuf::from)))
+        .map(|dir| (std::fs::canonicalize(&dir).unwrap_or(dir), RecursiveMode::NonRecursive))
+        .collect();
+    watcher.watch(dirs);
+}
+
+#[tauri::command]
+async fn workspace_list(workspace: State<'_, Workspace>) -> Result<WorkspaceInfo, PotassiumError> {
+    Ok(workspace.snapshot())
+}
+
+#[tauri::command]
+async fn workspace_new(
+    app: AppHandle,
+    workspace: State<'_, Workspace>,
+    name: Option<String>,
+    content: String,
+) -> Result<TabContent, PotassiumError> {
+    let result = workspace.create(name.as_deref().unwrap_or("Untitled"), &content);
+    watch_workspace(&app);
+    result
+}
+
+// Opens `path`, or asks for a file when it is not given.
+// Returns None if the dialog was cancelled.
+#[tauri::command]
+async fn workspace_open(
+    app: AppHandle,
+    workspace: State<'_, Workspace>,
+    path: Option<String>,
+) -> Result<Option<TabContent>, PotassiumError> {
+    let path = match path {
+        Some(path) => Some(PathBuf::from(path)),
+        None => rfd::FileDialog::new()
+            .set_directory(workspace.last_dir())
+            .add_filter("Lua Scripts", &["lua", "luau", "txt"])
+            .add_filter("All Files", &["*"])
+            .pick_file(),
+    };
+    let result = path.map(|p| workspace.open(&p)).transpose();
+    watch_workspace(&app);
+    result
+}
+
+#[tauri::command]
+async fn workspace_update(
+    workspace: State<'_, Workspace>,
+    id: String,
+    content: String,
+) -> Result<TabInfo, PotassiumError> {
+    workspace.update(&id, &content)
+}
+
+// Format-on-save: user saves pass `format: true` (autosave does
+// not) and, with format.formatOnSave on, the script is formatted
+// before it is written. Scripts that do not format are saved as
+// they are. Returns what was written.
+fn formatted_for_save(config: &ConfigStore, content: String, format: Option<bool>) -> String {
+    let settings = config.get().format;
+    if !format.unwrap_or(false) || !settings.format_on_save {
+        return content;
+    }
+    format::format(&content, &settings, None).unwrap_or(content)
+}
+
+#[tauri::command]
+async fn workspace_save(
+    workspace: State<'_, Workspace>,
+    config: State<'_, ConfigStore>,
+    id: String,
+    content: String,
+    format: Option<bool>,
+) -> Result<TabContent, PotassiumError> {
+    let content = formatted_for_save(&config, content, format);
+    let tab = workspace.save(&id, &content)?;
+    Ok(TabContent { tab, content })
+}
+
+// Asks where to save, then keeps the tab pointed at that file.
+// Returns None if the dialog was cancelled.
+#[tauri::command]
+async fn workspace_save_as(
+    app: AppHandle,
+    workspace: State<'_, Workspace>,
+    config: State<'_, ConfigStore>,
+    id: String,
+    content: String,
+    format: Option<bool>,
+) -> Result<Option<TabContent>, PotassiumError> {
+    let content = formatted_for_save(&config, content, format);
+    let current = workspace.path_of(&id);
+    let name = current
+        .as_ref()
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let path = rfd::FileDialog::new()
+        .set_directory(workspace.last_dir())
+        .set_file_name(&name)
+        .add_filter("Lua Scripts", &["lua", "luau", "txt"])
+        .save_file();
+    let result = path.map(|p| workspace.save_as(&id, &content, &p)).transpose();
+    watch_workspace(&app);
+    Ok(result?.map(|tab| TabContent { tab, content }))
+}
+
+#[tauri::command]
+async fn workspace_rename(
+    app: AppHandle,
+    workspace: State<'_, Workspace>,
+    id: String,
+    name: String,
+) -> Result<TabInfo, PotassiumError> {
+    let result = workspace.rename(&id, &name);
+    watch_workspace(&app);
+    result
+}
+
+#[tauri::command]
+async fn workspace_close(
+    app: AppHandle,
+    workspace: State<'_, Workspace>,
+    id: String,
+) -> Result<(), PotassiumError> {
+    let result = workspace.close(&id);
+    watch_workspace(&app);
+    result
+}
+
 #[tauri::command]
-async fn check_port(port: String) -> bool {
-    let addr = format!("127.0.0.1:{}", port);
-    TcpStream::connect_timeout(&addr.parse().unwrap(), Duration::from_millis(400)).is_ok()
+async fn workspace_set_execute_on_save(
+    workspace: State<'_, Workspace>,
+    id: String,
+    enabled: bool,
+) -> Result<TabInfo, PotassiumError> {
+    workspace.set_execute_on_save(&id, enabled)
+}
+
+#[tauri::command]
+async fn workspace_set_active(workspace: State<'_, Workspace>, id: String) -> Result<(), PotassiumError> {
+    workspace.set_active(&id)
+}
+
+// ─────────────────────────────────────────────────────────────
+// LIBRARY
+// A curated folder of scripts (config "libraryDir", default
+// <workspace dir>/Library) shown as a tree with tags and
+// favorites. Paths passed to these commands are relative to the
+// library root. Opening a script opens the file itself in a tab.
+// ─────────────────────────────────────────────────────────────
+fn library_dir(app: &AppHandle, config: &Config) -> PathBuf {
+    config.library_dir.clone().unwrap_or_else(|| workspace_dir(app, config).join("Library"))
+}
+
+#[tauri::command]
+async fn library_tree(library: State<'_, Library>) -> Result<LibraryFolder, PotassiumError> {
+    library.tree()
+}
+
+#[tauri::command]
+async fn library_search(library: State<'_, Library>, query: String) -> Result<Vec<SearchHit>, PotassiumError> {
+    Ok(library.search(&query))
+}
+
+#[tauri::command]
+async fn library_read(library: State<'_, Library>, path: String) -> Result<String, PotassiumError> {
+    library.read(&path)
+}
+
+#[tauri::command]
+async fn library_open(
+    app: AppHandle,
+    library: State<'_, Library>,
+    workspace: State<'_, Workspace>,
+    path: String,
+) -> Result<TabContent, PotassiumError> {
+    let result = workspace.open(&library.path_of(&path)?);
+    watch_workspace(&app);
+    result
+}
+
+#[tauri::command]
+async fn library_add(
+    library: State<'_, Library>,
+    folder: Option<String>,
+    name: String,
+    content: String,
+) -> Result<LibraryScript, PotassiumError> {
+    library.add(folder.as_deref().unwrap_or(""), &name, &content)
+}
+
+#[tauri::command]
+async fn library_set_tags(
+    library: State<'_, Library>,
+    path: String,
+    tags: Vec<String>,
+) -> Result<LibraryScript, PotassiumError> {
+    library.set_tags(&path, &tags)
+}
+
+#[tauri::command]
+async fn library_set_favorite(
+    library: State<'_, Library>,
+    path: String,
+    favorite: bool,
+) -> Result<LibraryScript, PotassiumError> {
+    library.set_favorite(&path, favorite)
+}
+
+// ─────────────────────────────────────────────────────────────
+// AUTOEXEC
+// Every time a port becomes attached (OpiumwareAttach, auto-
+// attach, probe, the control API), the enabled scripts in the
+// autoexec dir run against it in filename order through the
+// normal execute path. Each run is logged and emitted as
+// "autoexec-ran".
+// ─────────────────────────────────────────────────────────────
+fn autoexec_dir(app: &AppHandle, config: &Config) -> PathBuf {
+    config.autoexec.dir.clone().unwrap_or_else(|| workspace_dir(app, config).join("autoexec"))
+}
+
+fn spawn_autoexec(app: &AppHandle, config: &Config, port: &str) {
+    if !config.autoexec.enabled {
+        return;
+    }
+    let (app, port) = (app.clone(), port.to_string());
+    let dir = autoexec_dir(&app, config);
+    let settings = config.autoexec.clone();
+
+    tauri::async_runtime::spawn(async move {
+        let execute = |path: PathBuf, code: String| {
+            let (app, port, dir) = (app.clone(), port.clone(), dir.clone());
+            async move {
+                let report = execute_in_session(
+                    &app.state::<ConfigStore>(),
+                    &app.state::<BackendRegistry>(),
+                    &app.state::<Session>(),
+                    &app.state::<Arc<ConnectionPool>>(),
+                    &app.state::<LastBundle>(),
+                    Script::Source { code: &code, path: Some(&path), root: &dir },
+                    &port,
+                )
+                .await;
+                if let Ok(report) = &report {
+                    emit_report(&app, report);
+                }
+                report
+            }
+        };
+        let on_run = |run: &AutoexecRun| {
+            match &run.error {
+                None => println!("[Potassium] Autoexec {} ran on port {}", run.file, run.port),
+                Some(e) => eprintln!("[Potassium] Autoexec {} failed on port {}: {}", run.file, run.port, e),
+            }
+            app.state::<AutoexecLog>().record(run.clone());
+            app.state::<ControlServer>().publish(AUTOEXEC_RAN, run);
+            let _ = app.emit(AUTOEXEC_RAN, run);
+        };
+        autoexec::run(&dir, &settings, &port, execute, on_run).await;
+    });
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AutoexecInfo {
+    enabled: bool,
+    dir: String,
+    files: Vec<AutoexecFile>,
+    log: Vec<AutoexecRun>,
+}
+
+fn autoexec_info(app: &AppHandle) -> AutoexecInfo {
+    let config = app.state::<ConfigStore>().get();
+    let dir = autoexec_dir(app, &config);
+    AutoexecInfo {
+        enabled: config.autoexec.enabled,
+        dir: dir.display().to_string(),
+        files: autoexec::list(&dir, &config.autoexec),
+        log: app.state::<AutoexecLog>().snapshot(),
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// COMMAND: get_autoexec / set_autoexec_enabled /
+//          set_autoexec_file / clear_autoexec_log
+// get_autoexec creates the autoexec dir so it can be found.
+// ─────────────────────────────────────────────────────────────
+#[tauri::command]
+async fn get_autoexec(app: AppHandle) -> Result<AutoexecInfo, PotassiumError> {
+    let dir = autoexec_dir(&app, &app.state::<ConfigStore>().get());
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| PotassiumError::File { path: dir.display().to_string(), message: e.to_string() })?;
+    Ok(autoexec_info(&app))
+}
+
+#[tauri::command]
+async fn set_autoexec_enabled(app: AppHandle, enabled: bool) -> Result<AutoexecInfo, PotassiumError> {
+    let store = app.state::<ConfigStore>();
+    let mut config = store.get();
+    config.autoexec.enabled = enabled;
+    store.set(config)?;
+    Ok(autoexec_info(&app))
+}
+
+#[tauri::command]
+async fn set_autoexec_file(app: AppHandle, name: String, enabled: bool) -> Result<AutoexecInfo, PotassiumError> {
+    let store = app.state::<ConfigStore>();
+    let mut config = store.get();
+    if enabled {
+        config.autoexec.disabled.remove(&name);
+    } else {
+        config.autoexec.disabled.insert(name);
+    }
+    store.set(config)?;
+    Ok(autoexec_info(&app))
+}
+
+#[tauri::command]
+async fn clear_autoexec_log(app: AppHandle) -> Result<AutoexecInfo, PotassiumError> {
+    app.state::<AutoexecLog>().clear();
+    Ok(autoexec_info(&app))
 }
 
 // ─────────────────────────────────────────────────────────────
@@ -221,158 +1016,74 @@ async fn save_file_dialog(
 // ─────────────────────────────────────────────────────────────
 // COMMAND: js_api_call
 // Unified bridge for the JavaScript API backend.
-// op = "execute" | "attach" | "detach" | "check"
-// Implements the exact same logic as the user's JS API:
-//   - Net.createConnection equivalent via TcpStream
-//   - Zlib.deflate equivalent via ZlibEncoder (zlib/deflate)
-//   - Same port scanning loop, same return strings
-// ─────────────────────────────────────────────────────────────
-#[tauri::command]
-async fn js_api_call(op: String, code: String, port: String) -> String {
-    match op.as_str() {
-
-        // ── execute: mirrors your JS execute() exactly ──
-	        "execute" => {
-	            let ports_to_check: Vec<String> = match port.as_str() {
-	                "ALL" => PORTS.iter().map(|s| s.to_string()).collect(),
-	                _     => vec![port.clone()],
-	            };
-
-            fn send_bytes(stream: &mut TcpStream, message: &str) -> Result<(), String> {
-                let compressed = compress_data(message.as_bytes()).map_err(|e| e.to_string())?;
-                stream.write_all(&compressed).map_err(|e| e.to_string())?;
-                println!("Script sent ({} bytes)", compressed.len());
-                Ok(())
-            }
-
-            // When explicitly executing to ALL ports, actually send to every reachable port.
-            // (The original JS prototype scanned for the first open port, but the UI setting
-            // "All Ports" expects broadcast behavior.)
-            if port.as_str() == "ALL" && code != "NULL" {
-                let mut any_success = false;
-                let mut last_error  = String::new();
-                let mut success_ports: Vec<String> = Vec::new();
-
-                for p in &ports_to_check {
-                    let addr = format!("127.0.0.1:{}", p);
-                    let sock_addr: std::net::SocketAddr = match addr.parse() {
-                        Ok(a) => a, Err(_) => continue,
-                    };
-
-                    match TcpStream::connect_timeout(&sock_addr, Duration::from_millis(800)) {
-                        Ok(mut stream) => {
-                            println!("Successfully connected to Opiumware on port: {}", p);
-                            match send_bytes(&mut stream, &code) {
-                                Ok(_) => {
-                                    any_success = true;
-                                    success_ports.push(p.clone());
-                                }
-                                Err(e) => {
-                                    last_error = format!("Error sending script: {}", e);
-                                    eprintln!("[Potassium] {}", last_error);
-                                }
-                            }
-                            drop(stream);
-                        }
-                        Err(e) => {
-                            last_error = format!("Failed to connect to port {}: {}", p, e);
-                            println!("[Potassium] {}", last_error);
-                        }
-                    }
-                }
-
-                return if any_success {
-                    if success_ports.len() == 1 {
-                        format!("Successfully connected to Opiumware on port: {}", success_ports[0])
-                    } else {
-                        format!("Successfully executed on ports: {}", success_ports.join(", "))
-                    }
-                } else {
-                    format!("Failed to connect on all ports. Last error: {}", last_error)
-                };
-            }
-
-            let mut connected_port: Option<String> = None;
-            let mut stream_holder: Option<TcpStream> = None;
-
-            // Mirror: for (const P of ports) { try { Stream = connect... break } catch {} }
-            for p in &ports_to_check {
-                let addr = format!("127.0.0.1:{}", p);
-                let sock_addr: std::net::SocketAddr = match addr.parse() {
-                    Ok(a) => a, Err(_) => continue,
-                };
-                match TcpStream::connect_timeout(&sock_addr, Duration::from_millis(800)) {
-                    Ok(s) => {
-                        println!("Successfully connected to Opiumware on port: {}", p);
-                        connected_port = Some(p.clone());
-                        stream_holder  = Some(s);
-                        break; // mirror: break after first success
-                    }
-                    Err(e) => println!("Failed to connect to port {}: {}", p, e),
-                }
-            }
-
-            // Mirror: if (!Stream) return "Failed to connect on all ports"
-            let mut stream = match stream_holder {
-                Some(s) => s,
-                None    => return "Failed to connect on all ports".to_string(),
-            };
+// op = "execute" | "probe" | "attach" | "detach" | "check"
+// Same transport as the Rust commands. "execute" and "probe"
+// return an ExecutionReport; the other ops keep the JS API's
+// strings. For compatibility, "execute" with the code "NULL"
+// still means "probe". `id` is the tab an "execute" came from,
+// as for OpiumwareExecution.
+// ─────────────────────────────────────────────────────────────
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn js_api_call(
+    app: AppHandle,
+    config: State<'_, ConfigStore>,
+    session: State<'_, Session>,
+    pool: State<'_, Arc<ConnectionPool>>,
+    op: String,
+    code: String,
+    port: String,
+    id: Option<String>,
+) -> Result<Value, PotassiumError> {
+    let registry = app.state::<BackendRegistry>();
+    let settings = config.get();
+    let client = settings.client();
+    let backend = registry.get(&settings.backend)?;
+    // Compatibility shim: the JS API probed by executing "NULL".
+    let op = match op.as_str() {
+        "execute" if code == LEGACY_PROBE_SCRIPT => "probe",
+        op => op,
+    };
 
-            // Mirror: if (Code !== "NULL") { send... }
-            if code != "NULL" {
-                if let Err(e) = send_bytes(&mut stream, &code) {
-                    drop(stream);
-                    return format!("Error sending script: {}", e);
-                }
-            }
+    Ok(match op {
 
-            drop(stream);
+        // ── probe: same ExecutionReport as the probe command ──
+        "probe" => {
+            let report = probe_in_session(&app, &config, &registry, &session, &pool, &port).await?;
+            serde_json::to_value(report).unwrap_or_default()
+        }
 
-            // Mirror: return `Successfully connected to Opiumware on port: ${ConnectedPort}`
-            match connected_port {
-                Some(p) => format!("Successfully connected to Opiumware on port: {}", p),
-                None    => "Failed to connect on all ports".to_string(),
-            }
+        // ── execute: same ExecutionReport as OpiumwareExecution ──
+        "execute" => {
+            let workspace = app.state::<Workspace>();
+            let path = id.and_then(|id| workspace.path_of(&id));
+            let script = Script::Source { code: &code, path: path.as_deref(), root: &workspace.dir() };
+            let last_bundle = app.state::<LastBundle>();
+            let report = execute_in_session(&config, &registry, &session, &pool, &last_bundle, script, &port).await?;
+            emit_report(&app, &report);
+            serde_json::to_value(report).unwrap_or_default()
         }
 
         // ── attach: scan all ports, return first reachable ──
-        "attach" => {
-            for p in PORTS {
-                let addr = format!("127.0.0.1:{}", p);
-                let sock_addr: std::net::SocketAddr = match addr.parse() {
-                    Ok(a) => a, Err(_) => continue,
-                };
-                match TcpStream::connect_timeout(&sock_addr, Duration::from_millis(800)) {
-                    Ok(_) => {
-                        println!("[JS API] Attached on port {}", p);
-                        return format!("Successfully connected to Opiumware on port: {}", p);
-                    }
-                    Err(e) => println!("[JS API] Port {} unavailable: {}", p, e),
-                }
+        "attach" => match discover_first(backend.as_ref(), &client).await {
+            Ok(p) => {
+                attach_port(&app, &settings, &session, &pool, &p);
+                format!("Successfully connected to Opiumware on port: {}", p).into()
             }
-            "Failed to connect on all ports".to_string()
-        }
+            Err(_) => "Failed to connect on all ports".into(),
+        },
 
         // ── detach: logical disconnect, no bytes sent ──
         "detach" => {
-            println!("[JS API] Detached from port {}", port);
-            format!("Detached from port {}", port)
+            detach_port(&session, &pool, &port);
+            format!("Detached from port {}", port).into()
         }
 
         // ── check: returns "true" or "false" as string ──
-        "check" => {
-            let addr = format!("127.0.0.1:{}", port);
-            let sock_addr: std::net::SocketAddr = match addr.parse() {
-                Ok(a) => a, Err(_) => return "false".to_string(),
-            };
-            match TcpStream::connect_timeout(&sock_addr, Duration::from_millis(400)) {
-                Ok(_)  => "true".to_string(),
-                Err(_) => "false".to_string(),
-            }
-        }
+        "check" => backend.probe(&client, &port).await.is_ok().to_string().into(),
 
-        _ => format!("Unknown op: {}", op),
-    }
+        _ => format!("Unknown op: {}", op).into(),
+    })
 }
 
 // ─────────────────────────────────────────────────────────────
@@ -380,18 +1091,126 @@ async fn js_api_call(op: String, code: String, port: String) -> String {
 // ─────────────────────────────────────────────────────────────
 fn main() {
     tauri::Builder::default()
+        .setup(|app| {
+            let config_dir = app.path().app_config_dir()?;
+            let config = ConfigStore::load(config_dir.join("config.json"));
+            let dir = workspace_dir(app.handle(), &config.get());
+            app.manage(Workspace::load(dir, config_dir.join(WORKSPACE_FILE)));
+            app.manage(Library::load(library_dir(app.handle(), &config.get())));
+            app.manage(config);
+
+            // Open files edited elsewhere: the tab reloads, or runs
+            // if it has execute-on-save set (see the frontend).
+            let watch_handle = app.handle().clone();
+            match ScriptWatcher::new(move |path| {
+                if let Some(change) = watch_handle.state::<Workspace>().sync_from_disk(&path) {
+                    println!("[Potassium] {} changed on disk", change.tab.path);
+                    let _ = watch_handle.emit(FILE_CHANGED, &change);
+                }
+            }) {
+                Ok(watcher) => {
+                    app.manage(watcher);
+                    watch_workspace(app.handle());
+                }
+                Err(e) => eprintln!("[Potassium] File watcher unavailable: {}", e),
+            }
+            app.manage(Session::new());
+            app.manage(LastBundle::default());
+            app.manage(BackendRegistry::default());
+            app.manage(AutoexecLog::new());
+            app.manage(ControlServer::new());
+
+            let pool_handle = app.handle().clone();
+            let reply_handle = app.handle().clone();
+            let pool = ConnectionPool::new(move |event| {
+                pool_handle.state::<ControlServer>().publish(CONNECTION_STATE, &event);
+                let _ = pool_handle.emit(CONNECTION_STATE, &event);
+            })
+            .on_reply(move |mut event| {
+                event.text = reply_handle.state::<LastBundle>().annotate(&event.text);
+                reply_handle.state::<ControlServer>().publish(CONSOLE_OUTPUT, &event);
+                let _ = reply_handle.emit(CONSOLE_OUTPUT, &event);
+            });
+            app.manage(Arc::new(pool));
+
+            let monitor = Arc::new(InstanceMonitor::new());
+            app.manage(monitor.clone());
+            let settings_handle = app.handle().clone();
+            let emit_handle = app.handle().clone();
+            tauri::async_runtime::spawn(monitor.run(
+                move || {
+                    let config = settings_handle.state::<ConfigStore>().get();
+                    let backend = settings_handle
+                        .state::<BackendRegistry>()
+                        .get(&config.backend)
+                        .unwrap_or_else(|_| Arc::new(Opiumware));
+                    (backend, config.client(), config.monitor_interval())
+                },
+                move |event| {
+                    // A listener that went away is no longer attached.
+                    if !event.up {
+                        let pool = emit_handle.state::<Arc<ConnectionPool>>();
+                        detach_port(&emit_handle.state::<Session>(), &pool, &event.port);
+                    }
+                    emit_handle.state::<ControlServer>().publish(event.name(), &event);
+                    let _ = emit_handle.emit(event.name(), &event);
+                },
+            ));
+
+            let control_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move { restart_control(&control_handle).await });
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             OpiumwareAttach,
             OpiumwareExecution,
+            validate_script,
+            format_script,
+            bundle_script,
+            get_api_catalog,
+            lint_script,
             OpiumwareDetach,
             js_api_call,
             check_port,
+            get_instances,
+            get_connections,
+            get_session,
+            attach,
+            detach,
+            get_config,
+            set_config,
+            list_backends,
+            set_backend,
+            get_control_info,
+            reset_control_token,
+            probe,
             set_always_on_top,
             minimize_window,
             toggle_maximize,
             close_window,
             open_file_dialog,
             save_file_dialog,
+            workspace_list,
+            workspace_new,
+            workspace_open,
+            workspace_update,
+            workspace_save,
+            workspace_save_as,
+            workspace_rename,
+            workspace_close,
+            workspace_set_execute_on_save,
+            library_tree,
+            library_search,
+            library_read,
+            library_open,
+            library_add,
+            library_set_tags,
+            library_set_favorite,
+            get_autoexec,
+            set_autoexec_enabled,
+            set_autoexec_file,
+            clear_autoexec_log,
+            workspace_set_active,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,307 @@
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use flate2::{Decompress, FlushDecompress, Status};
+use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpSocket, TcpStream};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+use crate::codec::{Codec, Zlib};
+use crate::monitor::now_millis;
+use crate::transport::Client;
+
+/// Longest the accept loop waits before retrying after accept errors
+/// (e.g. out of file descriptors). The wait doubles from 10ms.
+const MAX_ACCEPT_BACKOFF: Duration = Duration::from_secs(1);
+
+// ─────────────────────────────────────────────────────────────
+// Behavior
+// ─────────────────────────────────────────────────────────────
+
+/// How one mock port treats incoming connections. The default accepts
+/// and records every script and sends nothing back.
+#[derive(Debug, Clone, Default)]
+pub struct MockBehavior {
+    /// Hold the port without listening, so connects are refused.
+    pub refuse: bool,
+    /// Wait this long before accepting each connection. The kernel still
+    /// completes the handshake; the script just sits unread meanwhile.
+    pub accept_delay: Option<Duration>,
+    /// Reset the connection after reading this many bytes.
+    pub reset_after: Option<usize>,
+    /// Written back after every script.
+    pub reply: Option<String>,
+    /// Zlib-compress `reply`.
+    pub compress_reply: bool,
+}
+
+impl MockBehavior {
+    pub fn refuse() -> Self {
+        Self { refuse: true, ..Self::default() }
+    }
+
+    pub fn slow_accept(delay: Duration) -> Self {
+        Self { accept_delay: Some(delay), ..Self::default() }
+    }
+
+    pub fn reset_after(bytes: usize) -> Self {
+        Self { reset_after: Some(bytes), ..Self::default() }
+    }
+
+    pub fn reply(text: impl Into<String>) -> Self {
+        Self { reply: Some(text.into()), ..Self::default() }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Received scripts
+// ─────────────────────────────────────────────────────────────
+
+/// One script as the mock received it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceivedScript {
+    pub port: String,
+    /// The inflated script, or the raw bytes (lossy UTF-8) if they were
+    /// not zlib.
+    pub script: String,
+    /// Bytes received on the wire for this script.
+    pub bytes: usize,
+    pub inflated: bool,
+    pub timestamp: u64,
+}
+
+type OnReceive = Box<dyn Fn(&ReceivedScript) + Send + Sync>;
+
+struct Shared {
+    received: Mutex<Vec<ReceivedScript>>,
+    notify: Notify,
+    on_receive: Option<OnReceive>,
+}
+
+impl Shared {
+    fn record(&self, script: ReceivedScript) {
+        if let Some(on_receive) = &self.on_receive {
+            on_receive(&script);
+        }
+        self.received.lock().unwrap_or_else(|e| e.into_inner()).push(script);
+        self.notify.notify_waiters();
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// MockListener
+// ─────────────────────────────────────────────────────────────
+
+/// Stand-in for Opiumware: binds ports, inflates incoming zlib payloads
+/// and records them. For integration tests and UI work without the real
+/// target. Stops listening when dropped.
+pub struct MockListener {
+    host: String,
+    ports: Vec<String>,
+    shared: Arc<Shared>,
+    tasks: Vec<JoinHandle<()>>,
+    /// Bound but never listening, so nothing else can take the
+    /// refused ports while the mock runs.
+    _reserved: Vec<TcpSocket>,
+}
+
+impl MockListener {
+    /// Binds every `(port, behavior)` pair on `host`. Port 0 picks a free
+    /// ephemeral port; `ports()` has the real numbers, in the same order.
+    pub async fn start(host: &str, ports: &[(u16, MockBehavior)]) -> io::Result<Self> {
+        Self::start_inner(host, ports, None).await
+    }
+
+    /// Like `start`, calling `on_receive` for each script as it arrives.
+    pub async fn start_with(
+        host: &str,
+        ports: &[(u16, MockBehavior)],
+        on_receive: impl Fn(&ReceivedScript) + Send + Sync + 'static,
+    ) -> io::Result<Self> {
+        Self::start_inner(host, ports, Some(Box::new(on_receive))).await
+    }
+
+    async fn start_inner(host: &str, ports: &[(u16, MockBehavior)], on_receive: Option<OnReceive>) -> io::Result<Self> {
+        let shared = Arc::new(Shared { received: Mutex::new(Vec::new()), notify: Notify::new(), on_receive });
+        let mut bound = Vec::new();
+        let mut tasks = Vec::new();
+        let mut reserved = Vec::new();
+
+        for (port, behavior) in ports {
+            if behavior.refuse {
+                let socket = reserve(host, *port).await?;
+                bound.push(socket.local_addr()?.port().to_string());
+                reserved.push(socket);
+                continue;
+            }
+            let listener = TcpListener::bind((host, *port)).await?;
+            let port = listener.local_addr()?.port().to_string();
+            tasks.push(tokio::spawn(accept_loop(listener, port.clone(), behavior.clone(), shared.clone())));
+            bound.push(port);
+        }
+
+        Ok(Self { host: host.to_string(), ports: bound, shared, tasks, _reserved: reserved })
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn ports(&self) -> &[String] {
+        &self.ports
+    }
+
+    /// A client pointed at this mock's host and ports.
+    pub fn client(&self) -> Client {
+        Client::new(self.host.clone(), self.ports.clone())
+    }
+
+    pub fn received(&self) -> Vec<ReceivedScript> {
+        self.shared.received.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Waits until at least `count` scripts have arrived or `limit` runs
+    /// out, then returns everything received so far.
+    pub async fn wait_for(&self, count: usize, limit: Duration) -> Vec<ReceivedScript> {
+        let _ = tokio::time::timeout(limit, async {
+            loop {
+                let notified = self.shared.notify.notified();
+                if self.received().len() >= count {
+                    return;
+                }
+                notified.await;
+            }
+        })
+        .await;
+        self.received()
+    }
+}
+
+impl Drop for MockListener {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// Binds `port` on `host` without listening. Connects to it are
+/// refused, and since the socket does not set `SO_REUSEADDR` no other
+/// listener can bind the port until it is dropped.
+async fn reserve(host: &str, port: u16) -> io::Result<TcpSocket> {
+    let addr = tokio::net::lookup_host((host, port))
+        .await?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("{} did not resolve", host)))?;
+    let socket = if addr.is_ipv4() { TcpSocket::new_v4()? } else { TcpSocket::new_v6()? };
+    socket.bind(addr)?;
+    Ok(socket)
+}
+
+async fn accept_loop(listener: TcpListener, port: String, behavior: MockBehavior, shared: Arc<Shared>) {
+    let mut backoff = Duration::ZERO;
+    loop {
+        if let Some(delay) = behavior.accept_delay {
+            tokio::time::sleep(delay).await;
+        }
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                backoff = Duration::ZERO;
+                tokio::spawn(handle(stream, port.clone(), behavior.clone(), shared.clone()));
+            }
+            // The peer gave up before we got to it; nothing to wait out.
+            Err(e) if matches!(e.kind(), io::ErrorKind::ConnectionAborted | io::ErrorKind::ConnectionReset) => {}
+            Err(_) => {
+                backoff = (backoff * 2).clamp(Duration::from_millis(10), MAX_ACCEPT_BACKOFF);
+                tokio::time::sleep(backoff).await;
+            }
+        }
+    }
+}
+
+/// Reads scripts off one connection until EOF. Several zlib streams
+/// back to back (a persistent connection) are recorded one by one;
+/// anything left over at EOF is recorded raw.
+async fn handle(mut stream: TcpStream, port: String, behavior: MockBehavior, shared: Arc<Shared>) {
+    let mut pending = Vec::new();
+    let mut total = 0usize;
+    let mut buf = [0u8; 4096];
+
+    loop {
+        let n = match stream.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        total += n;
+        pending.extend_from_slice(&buf[..n]);
+
+        if behavior.reset_after.is_some_and(|limit| total >= limit) {
+            let _ = stream.set_zero_linger();
+            return;
+        }
+
+        while let Some((script, used)) = inflate_prefix(&pending) {
+            pending.drain(..used);
+            shared.record(ReceivedScript {
+                port: port.clone(),
+                script: String::from_utf8_lossy(&script).into_owned(),
+                bytes: used,
+                inflated: true,
+                timestamp: now_millis(),
+            });
+            send_reply(&mut stream, &behavior).await;
+        }
+    }
+
+    if !pending.is_empty() {
+        shared.record(ReceivedScript {
+            port: port.clone(),
+            script: String::from_utf8_lossy(&pending).into_owned(),
+            bytes: pending.len(),
+            inflated: false,
+            timestamp: now_millis(),
+        });
+        send_reply(&mut stream, &behavior).await;
+    }
+}
+
+async fn send_reply(stream: &mut TcpStream, behavior: &MockBehavior) {
+    let Some(reply) = &behavior.reply else {
+        return;
+    };
+    let bytes = match behavior.compress_reply {
+        true => Zlib::default().encode(reply.as_bytes()).unwrap_or_default(),
+        false => reply.as_bytes().to_vec(),
+    };
+    // The client may not read replies and already be gone.
+    let _ = stream.write_all(&bytes).await;
+}
+
+/// Inflates one complete zlib stream from the start of `data`.
+/// Returns the output and how many input bytes it used, or `None` if
+/// the stream is incomplete or not zlib.
+fn inflate_prefix(data: &[u8]) -> Option<(Vec<u8>, usize)> {
+    if data.is_empty() {
+        return None;
+    }
+    let mut inflater = Decompress::new(true);
+    let mut out = Vec::with_capacity(data.len() * 4);
+    loop {
+        let consumed = inflater.total_in() as usize;
+        if out.len() == out.capacity() {
+            out.reserve(out.capacity().max(1024));
+        }
+        match inflater.decompress_vec(&data[consumed..], &mut out, FlushDecompress::None) {
+            Ok(Status::StreamEnd) => return Some((out, inflater.total_in() as usize)),
+            Ok(Status::BufError) | Err(_) => return None,
+            Ok(Status::Ok) if inflater.total_in() as usize == data.len() && out.len() < out.capacity() => {
+                return None
+            }
+            Ok(Status::Ok) => continue,
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::future::join_all;
+use serde::Serialize;
+
+use crate::backend::ExecutorBackend;
+use crate::transport::Client;
+
+pub const INSTANCE_UP: &str = "instance-up";
+pub const INSTANCE_DOWN: &str = "instance-down";
+
+/// Milliseconds since the Unix epoch, as sent to the frontend.
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+// ─────────────────────────────────────────────────────────────
+// Events
+// ─────────────────────────────────────────────────────────────
+
+/// A port came up or went down.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceEvent {
+    pub port: String,
+    pub up: bool,
+    pub timestamp: u64,
+}
+
+impl InstanceEvent {
+    /// Name of the Tauri event this is emitted as.
+    pub fn name(&self) -> &'static str {
+        if self.up {
+            INSTANCE_UP
+        } else {
+            INSTANCE_DOWN
+        }
+    }
+}
+
+/// Last known state of one port.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceStatus {
+    pub port: String,
+    pub up: bool,
+    /// When the port last changed state.
+    pub since: u64,
+}
+
+// ─────────────────────────────────────────────────────────────
+// InstanceMonitor
+// ─────────────────────────────────────────────────────────────
+
+/// Keeps probing the configured ports and reports transitions, so the
+/// frontend can react to pushes instead of polling `check_port`.
+#[derive(Debug, Default)]
+pub struct InstanceMonitor {
+    states: Mutex<BTreeMap<String, InstanceStatus>>,
+}
+
+impl InstanceMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current state of every port seen so far, in port order.
+    pub fn snapshot(&self) -> Vec<InstanceStatus> {
+        self.states.lock().unwrap_or_else(|e| e.into_inner()).values().cloned().collect()
+    }
+
+    pub fn is_up(&self, port: &str) -> bool {
+        self.states
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(port)
+            .is_some_and(|s| s.up)
+    }
+
+    /// Probes every port of `client` once through `backend` and returns
+    /// what changed.
+    ///
+    /// Ports seen down on the first poll produce no event; ports that
+    /// were up and have since been removed from the list produce a
+    /// down event.
+    pub async fn poll(&self, backend: &dyn ExecutorBackend, client: &Client) -> Vec<InstanceEvent> {
+        let results = join_all(client.ports().iter().map(|p| backend.probe(client, p))).await;
+        let timestamp = now_millis();
+
+        let mut states = self.states.lock().unwrap_or_else(|e| e.into_inner());
+        let mut events = Vec::new();
+
+        for (port, result) in client.ports().iter().zip(results) {
+            let up = result.is_ok();
+            let changed = match states.get(port) {
+                Some(prev) => prev.up != up,
+                None => up,
+            };
+            if changed {
+                events.push(InstanceEvent { port: port.clone(), up, timestamp });
+            }
+            if changed || !states.contains_key(port) {
+                states.insert(port.clone(), InstanceStatus { port: port.clone(), up, since: timestamp });
+            }
+        }
+
+        let removed: Vec<String> = states
+            .keys()
+            .filter(|p| !client.ports().contains(p))
+            .cloned()
+            .collect();
+        for port in removed {
+            if states.remove(&port).is_some_and(|s| s.up) {
+                events.push(InstanceEvent { port, up: false, timestamp });
+            }
+        }
+
+        events
+    }
+
+    /// Polls forever. `settings` is called before every poll so config
+    /// changes (backend, ports, host, interval) apply without a restart.
+    pub async fn run<F, E>(self: Arc<Self>, mut settings: F, emit: E)
+    where
+        F: FnMut() -> (Arc<dyn ExecutorBackend>, Client, Duration),
+        E: Fn(InstanceEvent),
+    {
+        loop {
+            let (backend, client, interval) = settings();
+            for event in self.poll(backend.as_ref(), &client).await {
+                println!("[Potassium] Port {} is {}", event.port, if event.up { "up" } else { "down" });
+                emit(event);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+
+use crate::error::PotassiumError;
+use crate::monitor::now_millis;
+use crate::reply::ConsoleEvent;
+use crate::transport::Client;
+
+pub const CONNECTION_STATE: &str = "connection-state";
+
+const BACKOFF_START: Duration = Duration::from_millis(250);
+const BACKOFF_MAX: Duration = Duration::from_secs(8);
+
+/// Delay before reconnect attempt number `attempt` (1-based): 250 ms,
+/// doubling up to 8 s.
+pub fn backoff(attempt: u32) -> Duration {
+    BACKOFF_START
+        .saturating_mul(1u32 << attempt.saturating_sub(1).min(6))
+        .min(BACKOFF_MAX)
+}
+
+// ─────────────────────────────────────────────────────────────
+// Events
+// ─────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConnectionState {
+    Connecting,
+    Connected,
+    Disconnected,
+    Reconnecting,
+}
+
+/// A pooled connection changed state. Emitted as `connection-state`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionEvent {
+    pub port: String,
+    pub state: ConnectionState,
+    /// Reconnect attempt number; 0 while connected.
+    pub attempt: u32,
+    pub timestamp: u64,
+}
+
+/// Current state of one pooled port.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionStatus {
+    pub port: String,
+    pub state: ConnectionState,
+}
+
+type EventSink = Arc<dyn Fn(ConnectionEvent) + Send + Sync>;
+type ReplySink = Arc<dyn Fn(ConsoleEvent) + Send + Sync>;
+
+// ─────────────────────────────────────────────────────────────
+// Per-port slot
+// ─────────────────────────────────────────────────────────────
+
+/// The write half of the current connection, and a way to tell the
+/// supervisor to give up on it.
+struct Live {
+    writer: OwnedWriteHalf,
+    dropped: Arc<Notify>,
+}
+
+struct Slot {
+    port: String,
+    writer: tokio::sync::Mutex<Option<Live>>,
+    state: Mutex<ConnectionState>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Slot {
+    fn set_state(&self, state: ConnectionState, attempt: u32, events: &EventSink) {
+        *self.state.lock().unwrap_or_else(|e| e.into_inner()) = state;
+        events(ConnectionEvent { port: self.port.clone(), state, attempt, timestamp: now_millis() });
+    }
+
+    fn state(&self) -> ConnectionState {
+        *self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// ConnectionPool
+// ─────────────────────────────────────────────────────────────
+
+/// Persistent connection mode: one long-lived connection per attached
+/// port, re-established with backoff whenever the listener drops it.
+///
+/// A supervisor task per port owns the read half and notices the drop;
+/// `send` borrows the write half. When a port has no live connection
+/// `send` returns `None` and the caller falls back to a one-shot connect.
+/// Anything the listener writes back is passed to `on_reply` as it arrives.
+pub struct ConnectionPool {
+    slots: Mutex<BTreeMap<String, Arc<Slot>>>,
+    events: EventSink,
+    replies: Option<ReplySink>,
+}
+
+impl ConnectionPool {
+    pub fn new(events: impl Fn(ConnectionEvent) + Send + Sync + 'static) -> Self {
+        Self { slots: Mutex::new(BTreeMap::new()), events: Arc::new(events), replies: None }
+    }
+
+    /// Forwards data read on pooled connections, one event per chunk.
+    pub fn on_reply(mut self, replies: impl Fn(ConsoleEvent) + Send + Sync + 'static) -> Self {
+        self.replies = Some(Arc::new(replies));
+        self
+    }
+
+    fn slot(&self, port: &str) -> Option<Arc<Slot>> {
+        self.slots.lock().unwrap_or_else(|e| e.into_inner()).get(port).cloned()
+    }
+
+    pub fn contains(&self, port: &str) -> bool {
+        self.slot(port).is_some()
+    }
+
+    /// Current state of every pooled port.
+    pub fn states(&self) -> Vec<ConnectionStatus> {
+        self.slots
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .map(|s| ConnectionStatus { port: s.port.clone(), state: s.state() })
+            .collect()
+    }
+
+    /// Starts keeping a connection open to `port`. No-op if already pooled.
+    pub fn open(&self, client: &Client, port: &str) {
+        let mut slots = self.slots.lock().unwrap_or_else(|e| e.into_inner());
+        if slots.contains_key(port) {
+            return;
+        }
+        let slot = Arc::new(Slot {
+            port: port.to_string(),
+            writer: tokio::sync::Mutex::new(None),
+            state: Mutex::new(ConnectionState::Connecting),
+            task: Mutex::new(None),
+        });
+        // The supervisor connects directly, never through a pool.
+        let task = tokio::spawn(supervise(
+            client.without_pool(),
+            slot.clone(),
+            self.events.clone(),
+            self.replies.clone(),
+        ));
+        *slot.task.lock().unwrap_or_else(|e| e.into_inner()) = Some(task);
+        slots.insert(port.to_string(), slot);
+    }
+
+    /// Stops the supervisor and drops the connection to `port`.
+    pub fn close(&self, port: &str) {
+        let slot = self.slots.lock().unwrap_or_else(|e| e.into_inner()).remove(port);
+        if let Some(slot) = slot {
+            if let Some(task) = slot.task.lock().unwrap_or_else(|e| e.into_inner()).take() {
+                task.abort();
+            }
+            slot.set_state(ConnectionState::Disconnected, 0, &self.events);
+        }
+    }
+
+    pub fn close_all(&self) {
+        let ports: Vec<String> = self.slots.lock().unwrap_or_else(|e| e.into_inner()).keys().cloned().collect();
+        for port in ports {
+            self.close(&port);
+        }
+    }
+
+    /// Writes `payload` over the pooled connection to `port`, giving up
+    /// after `limit`.
+    ///
+    /// Returns `None` if the port is not pooled or is between
+    /// reconnects. A failed or timed-out write drops the connection so
+    /// the supervisor reconnects.
+    pub async fn send(&self, port: &str, payload: &[u8], limit: Duration) -> Option<Result<(), PotassiumError>> {
+        let slot = self.slot(port)?;
+        let mut writer = slot.writer.lock().await;
+        let live = writer.as_mut()?;
+        let message = match timeout(limit, live.writer.write_all(payload)).await {
+            Ok(Ok(())) => return Some(Ok(())),
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => "write timed out".to_string(),
+        };
+        if let Some(live) = writer.take() {
+            live.dropped.notify_one();
+        }
+        Some(Err(PotassiumError::WriteFailed { port: port.to_string(), message }))
+    }
+}
+
+impl fmt::Debug for ConnectionPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConnectionPool").field("states", &self.states()).finish()
+    }
+}
+
+impl Drop for ConnectionPool {
+    fn drop(&mut self) {
+        for slot in self.slots.get_mut().unwrap_or_else(|e| e.into_inner()).values() {
+            if let Some(task) = slot.task.lock().unwrap_or_else(|e| e.into_inner()).take() {
+                task.abort();
+            }
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Supervisor
+// ─────────────────────────────────────────────────────────────
+
+async fn supervise(client: Client, slot: Arc<Slot>, events: EventSink, replies: Option<ReplySink>) {
+    let mut attempt = 0u32;
+    loop {
+        slot.set_state(
+            if attempt == 0 { ConnectionState::Connecting } else { ConnectionState::Reconnecting },
+            attempt,
+            &events,
+        );
+
+        match client.connect_port(&slot.port).await {
+            Ok(stream) => {
+                attempt = 0;
+                let (reader, writer) = stream.into_split();
+                let dropped = Arc::new(Notify::new());
+                *slot.writer.lock().await = Some(Live { writer, dropped: dropped.clone() });
+                slot.set_state(ConnectionState::Connected, 0, &events);
+                println!("[Potassium] Persistent connection to port {} open", slot.port);
+
+                tokio::select! {
+                    _ = read_until_closed(reader, &slot.port, replies.as_ref()) => {}
+                    _ = dropped.notified() => {}
+                }
+
+                *slot.writer.lock().await = None;
+                slot.set_state(ConnectionState::Disconnected, 0, &events);
+                println!("[Potassium] Persistent connection to port {} dropped", slot.port);
+            }
+            Err(e) => println!("[Potassium] Reconnect to port {} failed: {}", slot.port, e),
+        }
+
+        attempt = attempt.saturating_add(1);
+        tokio::time::sleep(backoff(attempt)).await;
+    }
+}
+
+/// Reads until the listener closes the connection or it errors,
+/// forwarding whatever it writes in the meantime.
+async fn read_until_closed(mut reader: OwnedReadHalf, port: &str, replies: Option<&ReplySink>) {
+    let mut buf = [0u8; 4096];
+    while let Ok(n) = reader.read(&mut buf).await {
+        if n == 0 {
+            break;
+        }
+        if let Some(replies) = replies {
+            replies(ConsoleEvent::new(port, String::from_utf8_lossy(&buf[..n])));
+        }
+    }
+}
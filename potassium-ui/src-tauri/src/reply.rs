@@ -0,0 +1,138 @@
+use std::io::Read;
+use std::time::Duration;
+
+use flate2::read::ZlibDecoder;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::time::Instant;
+
+use crate::error::PotassiumError;
+use crate::monitor::now_millis;
+
+pub const CONSOLE_OUTPUT: &str = "console-output";
+
+// ─────────────────────────────────────────────────────────────
+// Reply settings
+// ─────────────────────────────────────────────────────────────
+
+/// Whether and how to read what the listener writes back after a script.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReplyOptions {
+    pub enabled: bool,
+    /// How long to wait for the listener to finish replying.
+    pub timeout_ms: u64,
+    /// Anything past this many bytes is dropped and the reply marked truncated.
+    pub max_bytes: usize,
+    /// Zlib-inflate the reply before decoding it as text.
+    pub inflate: bool,
+}
+
+impl Default for ReplyOptions {
+    fn default() -> Self {
+        Self { enabled: false, timeout_ms: 500, max_bytes: 64 * 1024, inflate: false }
+    }
+}
+
+impl ReplyOptions {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms)
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Reply
+// ─────────────────────────────────────────────────────────────
+
+/// What a listener wrote back on the socket.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reply {
+    /// The reply decoded as UTF-8 (lossy).
+    pub text: String,
+    /// Bytes read off the socket, before inflating.
+    pub bytes: usize,
+    /// The reply hit `maxBytes` and was cut off.
+    pub truncated: bool,
+    /// The read stopped because `timeoutMs` ran out, not at EOF.
+    pub timed_out: bool,
+    pub inflated: bool,
+}
+
+/// Reads until EOF, `max_bytes` or the timeout, whichever comes first.
+///
+/// Hitting the timeout is not an error: whatever arrived before it is
+/// returned. An empty reply comes back as `None`.
+pub async fn read_reply<R>(reader: &mut R, port: &str, options: &ReplyOptions) -> Result<Option<Reply>, PotassiumError>
+where
+    R: AsyncRead + Unpin,
+{
+    let deadline = Instant::now() + options.timeout();
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    let mut truncated = false;
+    let mut timed_out = false;
+
+    loop {
+        let n = match tokio::time::timeout_at(deadline, reader.read(&mut buf)).await {
+            Ok(Ok(n)) => n,
+            Ok(Err(e)) => {
+                return Err(PotassiumError::ReadFailed { port: port.to_string(), message: e.to_string() })
+            }
+            Err(_) => {
+                timed_out = true;
+                break;
+            }
+        };
+        if n == 0 {
+            break;
+        }
+        let room = options.max_bytes - data.len();
+        data.extend_from_slice(&buf[..n.min(room)]);
+        if n >= room {
+            truncated = n > room;
+            break;
+        }
+    }
+
+    if data.is_empty() {
+        return Ok(None);
+    }
+
+    let bytes = data.len();
+    let (body, inflated) = match options.inflate {
+        true => match inflate(&data) {
+            Ok(body) => (body, true),
+            // Not zlib after all; show it as it came.
+            Err(_) => (data, false),
+        },
+        false => (data, false),
+    };
+
+    Ok(Some(Reply { text: String::from_utf8_lossy(&body).into_owned(), bytes, truncated, timed_out, inflated }))
+}
+
+pub fn inflate(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    ZlibDecoder::new(data).read_to_end(&mut out)?;
+    Ok(out)
+}
+
+// ─────────────────────────────────────────────────────────────
+// Console events
+// ─────────────────────────────────────────────────────────────
+
+/// Listener output for the console panel. Emitted as `console-output`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsoleEvent {
+    pub port: String,
+    pub text: String,
+    pub timestamp: u64,
+}
+
+impl ConsoleEvent {
+    pub fn new(port: impl Into<String>, text: impl Into<String>) -> Self {
+        Self { port: port.into(), text: text.into(), timestamp: now_millis() }
+    }
+}
@@ -0,0 +1,93 @@
+use serde::Serialize;
+
+use crate::error::PotassiumError;
+use crate::reply::Reply;
+
+// ─────────────────────────────────────────────────────────────
+// Per-port report
+// ─────────────────────────────────────────────────────────────
+
+/// What happened on one port during an execution.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortReport {
+    pub port: String,
+    pub connected: bool,
+    /// Bytes actually written to the socket.
+    pub bytes_sent: usize,
+    /// Size of the encoded payload, whether or not it was written.
+    pub compressed_size: usize,
+    pub duration_ms: u64,
+    pub error: Option<PotassiumError>,
+    /// What the listener wrote back, when replies are enabled.
+    pub reply: Option<Reply>,
+    /// Reading the reply failed. The script itself was still sent.
+    pub reply_error: Option<PotassiumError>,
+}
+
+impl PortReport {
+    pub fn new(port: impl Into<String>) -> Self {
+        Self {
+            port: port.into(),
+            connected: false,
+            bytes_sent: 0,
+            compressed_size: 0,
+            duration_ms: 0,
+            error: None,
+            reply: None,
+            reply_error: None,
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.connected && self.error.is_none()
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Execution report
+// ─────────────────────────────────────────────────────────────
+
+/// Result of one execute call, with an entry per port that was tried.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionReport {
+    /// The requested target: a port number or "ALL".
+    pub target: String,
+    /// True when the call only tested the connection.
+    pub probe: bool,
+    pub ports: Vec<PortReport>,
+    pub duration_ms: u64,
+    /// Non-fatal notes, e.g. executing on a port that is not attached.
+    pub warnings: Vec<String>,
+}
+
+impl ExecutionReport {
+    pub fn is_success(&self) -> bool {
+        self.ports.iter().any(PortReport::is_success)
+    }
+
+    pub fn success_ports(&self) -> Vec<&str> {
+        self.ports
+            .iter()
+            .filter(|p| p.is_success())
+            .map(|p| p.port.as_str())
+            .collect()
+    }
+
+    pub fn last_error(&self) -> Option<&PotassiumError> {
+        self.ports.iter().rev().find_map(|p| p.error.as_ref())
+    }
+
+    /// One-line summary in the wording the UI used before reports existed.
+    pub fn message(&self) -> String {
+        match self.success_ports().as_slice() {
+            [] => format!(
+                "Failed to connect on all ports. Last error: {}",
+                self.last_error().map(|e| e.to_string()).unwrap_or_default()
+            ),
+            [port] => format!("Successfully connected to Opiumware on port: {}", port),
+            ports => format!("Successfully executed on ports: {}", ports.join(", ")),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+use crate::error::PotassiumError;
+use crate::monitor::now_millis;
+use crate::report::ExecutionReport;
+use crate::transport::Target;
+
+// ─────────────────────────────────────────────────────────────
+// Session
+// ─────────────────────────────────────────────────────────────
+
+/// One port the user has attached to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachedPort {
+    pub port: String,
+    /// Unix millis of the attach.
+    pub attached_at: u64,
+    /// Unix millis of the last successful execution (or the attach).
+    pub last_activity: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    pub ports: Vec<AttachedPort>,
+}
+
+/// Which ports are attached, kept in the backend so it survives a
+/// webview reload. Managed as Tauri state.
+#[derive(Debug, Default)]
+pub struct Session {
+    attached: Mutex<BTreeMap<String, AttachedPort>>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn snapshot(&self) -> SessionInfo {
+        let attached = self.attached.lock().unwrap_or_else(|e| e.into_inner());
+        SessionInfo { ports: attached.values().cloned().collect() }
+    }
+
+    pub fn is_attached(&self, port: &str) -> bool {
+        self.attached.lock().unwrap_or_else(|e| e.into_inner()).contains_key(port)
+    }
+
+    /// Marks `port` as attached. Re-attaching keeps the original attach
+    /// time. The flag is true only for the call that attached the port,
+    /// even when several race.
+    pub fn attach(&self, port: &str) -> (AttachedPort, bool) {
+        let now = now_millis();
+        let mut attached = self.attached.lock().unwrap_or_else(|e| e.into_inner());
+        let newly_attached = !attached.contains_key(port);
+        let entry = attached.entry(port.to_string()).or_insert_with(|| AttachedPort {
+            port: port.to_string(),
+            attached_at: now,
+            last_activity: now,
+        });
+        entry.last_activity = now;
+        println!("[Potassium] Attached to port {}", port);
+        (entry.clone(), newly_attached)
+    }
+
+    /// Returns false if `port` was not attached.
+    pub fn detach(&self, port: &str) -> bool {
+        let removed = self.attached.lock().unwrap_or_else(|e| e.into_inner()).remove(port).is_some();
+        if removed {
+            println!("[Potassium] Detached from port {}", port);
+        }
+        removed
+    }
+
+    /// Checks an execute target against the session.
+    ///
+    /// With `require_attach` an unattached target is rejected; otherwise
+    /// the returned warnings are meant for `ExecutionReport::warnings`.
+    pub fn check_target(&self, target: &Target, require_attach: bool) -> Result<Vec<String>, PotassiumError> {
+        let attached = self.attached.lock().unwrap_or_else(|e| e.into_inner());
+        let missing = match target {
+            Target::Port(p) if !attached.contains_key(p) => p.clone(),
+            Target::All if attached.is_empty() => target.to_string(),
+            _ => return Ok(Vec::new()),
+        };
+        if require_attach {
+            return Err(PotassiumError::NotAttached { port: missing });
+        }
+        Ok(vec![match target {
+            Target::All => "No ports are attached".to_string(),
+            Target::Port(p) => format!("Port {} is not attached", p),
+        }])
+    }
+
+    /// Bumps `last_activity` on every attached port the report succeeded on.
+    pub fn record(&self, report: &ExecutionReport) {
+        let now = now_millis();
+        let mut attached = self.attached.lock().unwrap_or_else(|e| e.into_inner());
+        for port in report.success_ports() {
+            if let Some(entry) = attached.get_mut(port) {
+                entry.last_activity = now;
+            }
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use std::borrow::Cow;
+
+use full_moon::LuaVersion;
+use serde::Serialize;
+
+use crate::error::PotassiumError;
+
+/// Most diagnostics reported for one script. A single missing `end`
+/// can cascade into many errors; the first few are the useful ones.
+pub(crate) const MAX_DIAGNOSTICS: usize = 50;
+
+/// Marks a packet as a script. Editor code without a header gets one
+/// before it is sent (see `Opiumware::packet`).
+pub const SCRIPT_HEADER: &str = "OpiumwareScript";
+/// Marks a packet as a setting rather than Lua.
+pub const SETTING_HEADER: &str = "OpiumwareSetting";
+
+// ─────────────────────────────────────────────────────────────
+// Diagnostics
+// ─────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// One problem in a script. Lines and columns are 1-based, like Monaco
+/// markers; the end is exclusive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub message: String,
+    pub severity: Severity,
+    /// Lint rule that produced it; `None` for syntax errors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+}
+
+impl Diagnostic {
+    fn from_parse(error: &full_moon::Error) -> Self {
+        let (start, end) = error.range();
+        let message = match error {
+            // The tokenizer's Display appends the range; keep just the text.
+            full_moon::Error::TokenizerError(e) => e.error().to_string(),
+            other => other.error_message().into_owned(),
+        };
+        let (line, column) = (start.line(), start.character());
+        let (end_line, mut end_column) = (end.line().max(line), end.character());
+        if end_line == line && end_column <= column {
+            end_column = column + 1;
+        }
+        Diagnostic { line, column, end_line, end_column, message, severity: Severity::Error, rule: None }
+    }
+}
+
+/// Splits editor text into its `OpiumwareScript` header, with the
+/// whitespace around it (empty if there is none), and the Lua after it.
+/// `OpiumwareSetting` packets are not Lua and give `None`.
+pub(crate) fn split_header(code: &str) -> Option<(&str, &str)> {
+    let trimmed = code.trim_start();
+    if starts_with(trimmed, SETTING_HEADER) {
+        return None;
+    }
+    if !starts_with(trimmed, SCRIPT_HEADER) {
+        return Some(("", code));
+    }
+    let rest = trimmed[SCRIPT_HEADER.len()..].trim_start();
+    Some(code.split_at(code.len() - rest.len()))
+}
+
+/// The Lua in editor text, with any header blanked out so positions
+/// do not shift.
+pub(crate) fn lua_source(code: &str) -> Option<Cow<'_, str>> {
+    match split_header(code)? {
+        ("", _) => Some(Cow::Borrowed(code)),
+        (header, lua) => {
+            let blank: String = header.chars().map(|c| if c.is_whitespace() { c } else { ' ' }).collect();
+            Some(Cow::Owned(blank + lua))
+        }
+    }
+}
+
+/// Parses `code` as Luau and returns its syntax errors, in source order.
+/// Headers are handled as in `lua_source`.
+pub fn check(code: &str) -> Vec<Diagnostic> {
+    let Some(source) = lua_source(code) else {
+        return Vec::new();
+    };
+    let result = full_moon::parse_fallible(&source, LuaVersion::luau());
+    let mut diagnostics: Vec<Diagnostic> = result.errors().iter().map(Diagnostic::from_parse).collect();
+    diagnostics.sort_by_key(|d| (d.line, d.column));
+    diagnostics.dedup();
+    diagnostics.truncate(MAX_DIAGNOSTICS);
+    diagnostics
+}
+
+/// `Ok` if `code` parses, otherwise the first syntax error. Used by the
+/// execute path when `blockOnSyntaxError` is set.
+pub fn ensure_valid(code: &str) -> Result<(), PotassiumError> {
+    match check(code).into_iter().next() {
+        None => Ok(()),
+        Some(d) => Err(PotassiumError::Syntax { line: d.line, column: d.column, message: d.message }),
+    }
+}
+
+/// True if `code` (ignoring leading whitespace) starts with the
+/// `OpiumwareScript` or `OpiumwareSetting` header.
+pub fn has_header(code: &str) -> bool {
+    let code = code.trim_start();
+    starts_with(code, SCRIPT_HEADER) || starts_with(code, SETTING_HEADER)
+}
+
+fn starts_with(code: &str, header: &str) -> bool {
+    code.strip_prefix(header).is_some_and(|rest| !rest.starts_with(|c: char| c.is_alphanumeric() || c == '_'))
+}
@@ -0,0 +1,319 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::{AddrParseError, IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future::join_all;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+use crate::codec::{Codec, Zlib};
+use crate::error::PotassiumError;
+use crate::pool::ConnectionPool;
+use crate::reply::{read_reply, ReplyOptions};
+use crate::report::{ExecutionReport, PortReport};
+
+// ─────────────────────────────────────────────────────────────
+// Port list
+// ─────────────────────────────────────────────────────────────
+pub const PORTS: &[&str] = &["8392", "8393", "8394", "8395", "8396", "8397"];
+
+/// Script body the JS API used to mean "probe, send nothing". Only the
+/// compatibility shims still recognize it; `execute` sends it like any
+/// other script.
+pub const LEGACY_PROBE_SCRIPT: &str = "NULL";
+
+// ─────────────────────────────────────────────────────────────
+// Compression helper
+// ─────────────────────────────────────────────────────────────
+
+/// Encodes with the default codec (zlib, default level, no framing).
+pub fn compress_data(data: &[u8]) -> Result<Vec<u8>, PotassiumError> {
+    Zlib::default().encode(data)
+}
+
+/// Checks that `port` is a usable TCP port number.
+pub fn validate_port(port: &str) -> Result<u16, PotassiumError> {
+    match port.trim().parse::<u16>() {
+        Ok(n) if n != 0 => Ok(n),
+        _ => Err(PotassiumError::InvalidPort { port: port.to_string() }),
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Target
+// ─────────────────────────────────────────────────────────────
+
+/// Where an execution goes: a single port, or every known port.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    Port(String),
+    All,
+}
+
+impl Target {
+    /// Parses the `port` argument the frontend passes ("ALL" or a port number).
+    pub fn parse(port: &str) -> Self {
+        match port {
+            "ALL" => Target::All,
+            p => Target::Port(p.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::Port(p) => f.write_str(p),
+            Target::All => f.write_str("ALL"),
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Client
+// ─────────────────────────────────────────────────────────────
+
+/// Connects to Opiumware listeners and delivers scripts to them.
+///
+/// Every Tauri command, the JS API bridge and the reference
+/// implementation go through this type, so connection handling
+/// lives in exactly one place.
+#[derive(Debug, Clone)]
+pub struct Client {
+    host: String,
+    ports: Vec<String>,
+    connect_timeout: Duration,
+    probe_timeout: Duration,
+    /// Persistent connections to send over, when that mode is on.
+    pool: Option<Arc<ConnectionPool>>,
+    reply: ReplyOptions,
+    codec: Arc<dyn Codec>,
+    /// Per-port codec overrides from the port profiles.
+    port_codecs: BTreeMap<String, Arc<dyn Codec>>,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new("127.0.0.1", PORTS.iter().map(|s| s.to_string()).collect())
+    }
+}
+
+impl Client {
+    pub fn new(host: impl Into<String>, ports: Vec<String>) -> Self {
+        Self {
+            host: host.into(),
+            ports,
+            connect_timeout: Duration::from_millis(800),
+            probe_timeout: Duration::from_millis(400),
+            pool: None,
+            reply: ReplyOptions::default(),
+            codec: Arc::new(Zlib::default()),
+            port_codecs: BTreeMap::new(),
+        }
+    }
+
+    /// Codec for every port without its own.
+    pub fn with_codec(mut self, codec: Arc<dyn Codec>) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    pub fn with_port_codec(mut self, port: impl Into<String>, codec: Arc<dyn Codec>) -> Self {
+        self.port_codecs.insert(port.into(), codec);
+        self
+    }
+
+    pub fn codec_for(&self, port: &str) -> &dyn Codec {
+        self.port_codecs.get(port).unwrap_or(&self.codec).as_ref()
+    }
+
+    /// Reads the listener's reply after each one-shot send. Replies on
+    /// pooled connections arrive through the pool instead.
+    pub fn with_reply(mut self, reply: ReplyOptions) -> Self {
+        self.reply = reply;
+        self
+    }
+
+    /// Sends scripts over `pool`'s open connections where it has one,
+    /// falling back to a fresh connection per execute otherwise.
+    pub fn with_pool(mut self, pool: Arc<ConnectionPool>) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    pub(crate) fn without_pool(&self) -> Self {
+        Self { pool: None, ..self.clone() }
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn ports(&self) -> &[String] {
+        &self.ports
+    }
+
+    async fn connect(&self, port: &str, limit: Duration) -> Result<TcpStream, PotassiumError> {
+        let port_num = validate_port(port)?;
+        let ip: IpAddr = self
+            .host
+            .parse()
+            .map_err(|e: AddrParseError| PotassiumError::AddrParse { addr: self.host.clone(), message: e.to_string() })?;
+        let addr = SocketAddr::new(ip, port_num);
+        match timeout(limit, TcpStream::connect(addr)).await {
+            Ok(Ok(stream)) => Ok(stream),
+            Ok(Err(e)) => Err(PotassiumError::from_connect(port, &e)),
+            Err(_) => Err(PotassiumError::Timeout { port: port.to_string() }),
+        }
+    }
+
+    /// Opens a connection to `port` with the normal connect timeout.
+    pub(crate) async fn connect_port(&self, port: &str) -> Result<TcpStream, PotassiumError> {
+        self.connect(port, self.connect_timeout).await
+    }
+
+    /// Succeeds if something is listening on `port`.
+    pub async fn probe(&self, port: &str) -> Result<(), PotassiumError> {
+        self.connect(port, self.probe_timeout).await.map(drop)
+    }
+
+    /// Probes every port at once and returns the first reachable one
+    /// in list order. Takes at most one probe timeout.
+    pub async fn attach_any(&self) -> Result<String, PotassiumError> {
+        let results = join_all(self.ports.iter().map(|p| self.probe(p))).await;
+        self.ports
+            .iter()
+            .zip(results)
+            .find_map(|(port, result)| result.is_ok().then(|| port.clone()))
+            .ok_or(PotassiumError::NoInstanceFound)
+    }
+
+    fn target_ports(&self, target: &Target) -> Result<Vec<String>, PotassiumError> {
+        match target {
+            Target::All => Ok(self.ports.clone()),
+            Target::Port(p) => {
+                validate_port(p)?;
+                Ok(vec![p.clone()])
+            }
+        }
+    }
+
+    /// Sends `code` to the target. `Target::All` broadcasts to every
+    /// port in the list in parallel and returns once the slowest port
+    /// has finished or timed out.
+    ///
+    /// Per-port failures are recorded in the report, and anything worth
+    /// knowing that did not fail the send goes in its warnings. Only a
+    /// malformed target or an uncompressible script is returned as an
+    /// error.
+    pub async fn execute(&self, code: &str, target: &Target) -> Result<ExecutionReport, PotassiumError> {
+        let started = Instant::now();
+        let ports_to_check = self.target_ports(target)?;
+
+        // Encode once for the default codec, plus once per port that
+        // has its own.
+        let default_payload = self.codec.encode(code.as_bytes())?;
+        let mut payloads: BTreeMap<&str, Vec<u8>> = BTreeMap::new();
+        for port in &ports_to_check {
+            if let Some(codec) = self.port_codecs.get(port) {
+                payloads.insert(port, codec.encode(code.as_bytes())?);
+            }
+        }
+
+        let results = join_all(ports_to_check.iter().map(|p| {
+            let payload = payloads.get(p.as_str()).unwrap_or(&default_payload);
+            self.execute_on(p, Some(payload))
+        }))
+        .await;
+        let (ports, warnings): (Vec<_>, Vec<_>) = results.into_iter().unzip();
+
+        Ok(ExecutionReport {
+            target: target.to_string(),
+            probe: false,
+            ports,
+            duration_ms: started.elapsed().as_millis() as u64,
+            warnings: warnings.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Connects to the target's ports without sending anything, and
+    /// reports which ones answered.
+    pub async fn probe_target(&self, target: &Target) -> Result<ExecutionReport, PotassiumError> {
+        let started = Instant::now();
+        let ports_to_check = self.target_ports(target)?;
+        let ports = join_all(ports_to_check.iter().map(|p| self.execute_on(p, None))).await;
+        let ports = ports.into_iter().map(|(port, _)| port).collect();
+
+        Ok(ExecutionReport {
+            target: target.to_string(),
+            probe: true,
+            ports,
+            duration_ms: started.elapsed().as_millis() as u64,
+            warnings: Vec::new(),
+        })
+    }
+
+    /// Sends `payload` (or only connects) and reports on it, plus a
+    /// warning if the pooled connection had to be replaced.
+    async fn execute_on(&self, port: &str, payload: Option<&[u8]>) -> (PortReport, Option<String>) {
+        let started = Instant::now();
+        let mut report = PortReport::new(port);
+        let mut warning = None;
+
+        if let (Some(pool), Some(payload)) = (&self.pool, payload) {
+            match pool.send(port, payload, self.connect_timeout).await {
+                Some(Ok(())) => {
+                    report.connected = true;
+                    report.compressed_size = payload.len();
+                    report.bytes_sent = payload.len();
+                    report.duration_ms = started.elapsed().as_millis() as u64;
+                    return (report, None);
+                }
+                // The pooled connection just dropped; try a fresh one.
+                Some(Err(e)) => {
+                    warning = Some(format!("Persistent connection to port {} dropped ({}), sent on a new one", port, e))
+                }
+                None => {}
+            }
+        }
+
+        match self.connect(port, self.connect_timeout).await {
+            Ok(mut stream) => {
+                report.connected = true;
+                if let Some(payload) = payload {
+                    report.compressed_size = payload.len();
+                    let written = match timeout(self.connect_timeout, stream.write_all(payload)).await {
+                        Ok(result) => result.map_err(|e| e.to_string()),
+                        Err(_) => Err("write timed out".to_string()),
+                    };
+                    match written {
+                        Ok(()) => {
+                            report.bytes_sent = payload.len();
+                            if self.reply.enabled {
+                                // Signal end of script so the listener can answer.
+                                let _ = stream.shutdown().await;
+                                match read_reply(&mut stream, port, &self.reply).await {
+                                    Ok(reply) => report.reply = reply,
+                                    Err(e) => report.reply_error = Some(e),
+                                }
+                            }
+                        }
+                        Err(message) => {
+                            report.error = Some(PotassiumError::WriteFailed {
+                                port: port.to_string(),
+                                message,
+                            });
+                        }
+                    }
+                }
+            }
+            Err(e) => report.error = Some(e),
+        }
+
+        report.duration_ms = started.elapsed().as_millis() as u64;
+        (report, warning)
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// Emitted with a `FileChange` when an open file changes on disk.
+pub const FILE_CHANGED: &str = "file-changed";
+
+/// Editors save in bursts (truncate, write, rename); wait this long after
+/// the last event for a path before reporting it.
+const SETTLE: Duration = Duration::from_millis(150);
+
+// ─────────────────────────────────────────────────────────────
+// ScriptWatcher
+// ─────────────────────────────────────────────────────────────
+
+/// Watches directories and reports each changed file once its events
+/// settle. Directories rather than files are watched so editors that
+/// save by replacing the file are still seen. Managed as Tauri state.
+pub struct ScriptWatcher {
+    watcher: Mutex<RecommendedWatcher>,
+    watched: Mutex<BTreeMap<PathBuf, RecursiveMode>>,
+}
+
+impl ScriptWatcher {
+    /// `on_change` runs on a background thread with the path of every
+    /// file that was created or modified.
+    pub fn new(on_change: impl Fn(PathBuf) + Send + 'static) -> notify::Result<Self> {
+        let (tx, rx) = mpsc::channel::<PathBuf>();
+        let watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+            let Ok(event) = result else {
+                return;
+            };
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                for path in event.paths {
+                    let _ = tx.send(path);
+                }
+            }
+        })?;
+
+        thread::spawn(move || {
+            let mut pending = BTreeSet::new();
+            loop {
+                let next = if pending.is_empty() {
+                    rx.recv().map_err(|_| RecvTimeoutError::Disconnected)
+                } else {
+                    rx.recv_timeout(SETTLE)
+                };
+                match next {
+                    Ok(path) => {
+                        pending.insert(path);
+                    }
+                    Err(RecvTimeoutError::Timeout) => {
+                        for path in std::mem::take(&mut pending) {
+                            on_change(path);
+                        }
+                    }
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+        });
+
+        Ok(Self { watcher: Mutex::new(watcher), watched: Mutex::new(BTreeMap::new()) })
+    }
+
+    /// Watches exactly `dirs`: new entries are added, ones no longer
+    /// listed are dropped. Directories that do not exist are skipped.
+    pub fn watch(&self, dirs: BTreeMap<PathBuf, RecursiveMode>) {
+        let mut watcher = self.watcher.lock().unwrap_or_else(|e| e.into_inner());
+        let mut watched = self.watched.lock().unwrap_or_else(|e| e.into_inner());
+
+        let stale: Vec<PathBuf> =
+            watched.iter().filter(|(dir, mode)| dirs.get(*dir) != Some(mode)).map(|(dir, _)| dir.clone()).collect();
+        for dir in stale {
+            let _ = watcher.unwatch(&dir);
+            watched.remove(&dir);
+        }
+
+        for (dir, mode) in dirs {
+            if watched.contains_key(&dir) || !dir.is_dir() {
+                continue;
+            }
+            match watcher.watch(&dir, mode) {
+                Ok(()) => {
+                    watched.insert(dir, mode);
+                }
+                Err(e) => eprintln!("[Potassium] Cannot watch {}: {}", dir.display(), e),
+            }
+        }
+    }
+
+    pub fn watched(&self) -> Vec<PathBuf> {
+        self.watched.lock().unwrap_or_else(|e| e.into_inner()).keys().cloned().collect()
+    }
+}
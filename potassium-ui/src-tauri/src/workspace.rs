@@ -0,0 +1,476 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::PotassiumError;
+
+/// File in the app config dir listing the open tabs.
+pub const WORKSPACE_FILE: &str = "workspace.json";
+
+/// Folder next to `WORKSPACE_FILE` holding a copy of every unsaved
+/// buffer, so edits survive a restart.
+pub const SCRATCH_DIR: &str = "scratch";
+
+/// Added to tab names that have no extension.
+pub const SCRIPT_EXTENSION: &str = "lua";
+
+/// Files with one of these extensions count as scripts in folders the
+/// app scans (autoexec, the library).
+pub const SCRIPT_EXTENSIONS: &[&str] = &["lua", "luau", "txt"];
+
+pub fn is_script(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| SCRIPT_EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+}
+
+fn file_error(path: &Path, e: impl ToString) -> PotassiumError {
+    PotassiumError::File { path: path.display().to_string(), message: e.to_string() }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tabs
+// ─────────────────────────────────────────────────────────────
+
+/// One open tab as the frontend sees it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TabInfo {
+    pub id: String,
+    /// File name, shown on the tab.
+    pub name: String,
+    pub path: String,
+    /// The editor buffer differs from what is on disk.
+    pub dirty: bool,
+    /// Execute the file whenever it is saved, here or in another editor.
+    pub execute_on_save: bool,
+}
+
+/// A tab plus its current buffer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TabContent {
+    #[serde(flatten)]
+    pub tab: TabInfo,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceInfo {
+    /// Where new tabs are created.
+    pub dir: String,
+    pub active: Option<String>,
+    pub tabs: Vec<TabContent>,
+}
+
+/// What `workspace.json` holds. Contents stay in the files themselves,
+/// and unsaved buffers in `SCRATCH_DIR`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct SavedState {
+    tab_counter: u64,
+    active: Option<String>,
+    tabs: Vec<SavedTab>,
+    last_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SavedTab {
+    id: String,
+    path: PathBuf,
+    #[serde(default)]
+    execute_on_save: bool,
+}
+
+/// An open file changed on disk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChange {
+    #[serde(flatten)]
+    pub tab: TabInfo,
+    /// What is on disk now.
+    pub content: String,
+    /// The tab had unsaved edits. They were kept and the tab is dirty;
+    /// otherwise the buffer now matches `content`.
+    pub conflict: bool,
+}
+
+#[derive(Debug)]
+struct OpenFile {
+    id: String,
+    path: PathBuf,
+    /// Contents as last read from or written to disk.
+    saved: String,
+    buffer: String,
+    execute_on_save: bool,
+}
+
+impl OpenFile {
+    fn new(id: String, path: PathBuf, text: String) -> Self {
+        Self { id, path, saved: text.clone(), buffer: text, execute_on_save: false }
+    }
+
+    fn info(&self) -> TabInfo {
+        TabInfo {
+            id: self.id.clone(),
+            name: self.path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+            path: self.path.display().to_string(),
+            dirty: self.is_dirty(),
+            execute_on_save: self.execute_on_save,
+        }
+    }
+
+    fn content(&self) -> TabContent {
+        TabContent { tab: self.info(), content: self.buffer.clone() }
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.saved != self.buffer
+    }
+}
+
+/// Turns a tab name into a file name: no path separators, and `.lua`
+/// added if there is no extension.
+pub(crate) fn file_name(name: &str) -> Result<String, PotassiumError> {
+    let name = name.trim();
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        return Err(PotassiumError::File { path: name.to_string(), message: "not a valid file name".into() });
+    }
+    Ok(match Path::new(name).extension() {
+        Some(_) => name.to_string(),
+        None => format!("{}.{}", name, SCRIPT_EXTENSION),
+    })
+}
+
+fn scratch_path(scratch_dir: &Path, id: &str) -> PathBuf {
+    scratch_dir.join(format!("{}.{}", id, SCRIPT_EXTENSION))
+}
+
+// ─────────────────────────────────────────────────────────────
+// Workspace
+// ─────────────────────────────────────────────────────────────
+
+#[derive(Debug)]
+struct Inner {
+    dir: PathBuf,
+    counter: u64,
+    active: Option<String>,
+    files: Vec<OpenFile>,
+    last_dir: Option<PathBuf>,
+}
+
+impl Inner {
+    fn file(&mut self, id: &str) -> Result<&mut OpenFile, PotassiumError> {
+        self.files
+            .iter_mut()
+            .find(|f| f.id == id)
+            .ok_or_else(|| PotassiumError::File { path: id.to_string(), message: "no such tab".into() })
+    }
+
+    fn next_id(&mut self) -> String {
+        self.counter += 1;
+        format!("tab-{}", self.counter)
+    }
+
+    /// `dir/name`, or `dir/name (2)` etc. if that is taken.
+    fn unique_path(&self, name: &str) -> PathBuf {
+        let candidate = self.dir.join(name);
+        if !candidate.exists() {
+            return candidate;
+        }
+        let stem = Path::new(name).file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let ext = Path::new(name).extension().map(|e| e.to_string_lossy().into_owned());
+        (2..)
+            .map(|n| match &ext {
+                Some(ext) => self.dir.join(format!("{} ({}).{}", stem, n, ext)),
+                None => self.dir.join(format!("{} ({})", stem, n)),
+            })
+            .find(|p| !p.exists())
+            .unwrap_or(candidate)
+    }
+}
+
+/// Open tabs backed by real files. New tabs are created in the workspace
+/// dir; opened files stay where they are. The tab list survives restarts
+/// via `workspace.json`, unsaved edits via a scratch copy per tab.
+/// Managed as Tauri state.
+#[derive(Debug)]
+pub struct Workspace {
+    state_path: PathBuf,
+    scratch_dir: PathBuf,
+    inner: Mutex<Inner>,
+}
+
+impl Workspace {
+    /// Loads the tab list from `state_path`, with any unsaved edits from
+    /// the scratch dir next to it. Tabs whose file is gone are dropped.
+    pub fn load(dir: PathBuf, state_path: PathBuf) -> Self {
+        let scratch_dir = state_path.with_file_name(SCRATCH_DIR);
+        let state: SavedState = fs::read_to_string(&state_path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+
+        let files = state
+            .tabs
+            .into_iter()
+            .filter_map(|tab| {
+                let scratch = scratch_path(&scratch_dir, &tab.id);
+                match fs::read_to_string(&tab.path) {
+                    Ok(text) => {
+                        let mut file = OpenFile { execute_on_save: tab.execute_on_save, ..OpenFile::new(tab.id, tab.path, text) };
+                        if let Ok(buffer) = fs::read_to_string(&scratch) {
+                            file.buffer = buffer;
+                        }
+                        Some(file)
+                    }
+                    Err(e) => {
+                        eprintln!("[Potassium] Dropping tab {}: {}", tab.path.display(), e);
+                        let _ = fs::remove_file(&scratch);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        let inner = Inner { dir, counter: state.tab_counter, active: state.active, files, last_dir: state.last_dir };
+        Self { state_path, scratch_dir, inner: Mutex::new(inner) }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn persist(&self, inner: &Inner) {
+        let state = SavedState {
+            tab_counter: inner.counter,
+            active: inner.active.clone(),
+            tabs: inner
+                .files
+                .iter()
+                .map(|f| SavedTab { id: f.id.clone(), path: f.path.clone(), execute_on_save: f.execute_on_save })
+                .collect(),
+            last_dir: inner.last_dir.clone(),
+        };
+        let result = self
+            .state_path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&self.state_path, serde_json::to_string_pretty(&state).unwrap_or_default()));
+        if let Err(e) = result {
+            eprintln!("[Potassium] Failed to save workspace: {}", e);
+        }
+    }
+
+    /// Writes the scratch copy of a dirty tab, or removes it once the
+    /// buffer matches the file again.
+    fn keep_scratch(&self, file: &OpenFile) {
+        let path = scratch_path(&self.scratch_dir, &file.id);
+        let result = match file.is_dirty() {
+            true => fs::create_dir_all(&self.scratch_dir).and_then(|_| fs::write(&path, &file.buffer)),
+            false => match fs::remove_file(&path) {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                result => result,
+            },
+        };
+        if let Err(e) = result {
+            eprintln!("[Potassium] Failed to keep unsaved edits for {}: {}", file.path.display(), e);
+        }
+    }
+
+    pub fn dir(&self) -> PathBuf {
+        self.lock().dir.clone()
+    }
+
+    /// Changes where new tabs are created. Open tabs keep their files.
+    pub fn set_dir(&self, dir: PathBuf) {
+        self.lock().dir = dir;
+    }
+
+    /// Where open/save dialogs should start.
+    pub fn last_dir(&self) -> PathBuf {
+        let inner = self.lock();
+        inner.last_dir.clone().unwrap_or_else(|| inner.dir.clone())
+    }
+
+    pub fn snapshot(&self) -> WorkspaceInfo {
+        let inner = self.lock();
+        WorkspaceInfo {
+            dir: inner.dir.display().to_string(),
+            active: inner.active.clone(),
+            tabs: inner.files.iter().map(OpenFile::content).collect(),
+        }
+    }
+
+    /// The file behind tab `id`.
+    pub fn path_of(&self, id: &str) -> Option<PathBuf> {
+        self.lock().files.iter().find(|f| f.id == id).map(|f| f.path.clone())
+    }
+
+    /// Every open file's path.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.lock().files.iter().map(|f| f.path.clone()).collect()
+    }
+
+    /// Creates a new file in the workspace dir and opens it. A taken name
+    /// gets a " (2)"-style suffix.
+    pub fn create(&self, name: &str, content: &str) -> Result<TabContent, PotassiumError> {
+        let mut inner = self.lock();
+        let name = file_name(name)?;
+        fs::create_dir_all(&inner.dir).map_err(|e| file_error(&inner.dir, e))?;
+        let path = inner.unique_path(&name);
+        fs::write(&path, content).map_err(|e| file_error(&path, e))?;
+        let path = fs::canonicalize(&path).unwrap_or(path);
+
+        let id = inner.next_id();
+        let file = OpenFile::new(id.clone(), path, content.to_string());
+        let tab = file.content();
+        inner.files.push(file);
+        inner.active = Some(id);
+        self.persist(&inner);
+        Ok(tab)
+    }
+
+    /// Opens `path` in a new tab, or returns the tab that already has it.
+    pub fn open(&self, path: &Path) -> Result<TabContent, PotassiumError> {
+        let path = fs::canonicalize(path).map_err(|e| file_error(path, e))?;
+        let mut inner = self.lock();
+        inner.last_dir = path.parent().map(Path::to_path_buf);
+
+        let tab = match inner.files.iter().find(|f| f.path == path) {
+            Some(file) => file.content(),
+            None => {
+                let text = fs::read_to_string(&path).map_err(|e| file_error(&path, e))?;
+                let id = inner.next_id();
+                let file = OpenFile::new(id, path, text);
+                let tab = file.content();
+                inner.files.push(file);
+                tab
+            }
+        };
+        inner.active = Some(tab.tab.id.clone());
+        self.persist(&inner);
+        Ok(tab)
+    }
+
+    /// Records the editor buffer without writing it to the tab's file.
+    /// Unsaved buffers go to the scratch copy instead.
+    pub fn update(&self, id: &str, content: &str) -> Result<TabInfo, PotassiumError> {
+        let mut inner = self.lock();
+        let file = inner.file(id)?;
+        file.buffer = content.to_string();
+        self.keep_scratch(file);
+        Ok(file.info())
+    }
+
+    /// Writes `content` to the tab's file.
+    pub fn save(&self, id: &str, content: &str) -> Result<TabInfo, PotassiumError> {
+        let mut inner = self.lock();
+        let file = inner.file(id)?;
+        fs::write(&file.path, content).map_err(|e| file_error(&file.path, e))?;
+        file.saved = content.to_string();
+        file.buffer = content.to_string();
+        self.keep_scratch(file);
+        Ok(file.info())
+    }
+
+    /// Writes `content` to `path` and points the tab at it from now on.
+    /// Fails if another tab already has `path` open.
+    pub fn save_as(&self, id: &str, content: &str, path: &Path) -> Result<TabInfo, PotassiumError> {
+        let mut inner = self.lock();
+        inner.file(id)?;
+        // `path` may not exist yet; compare through its parent instead.
+        let target = match (path.parent(), path.file_name()) {
+            (Some(dir), Some(name)) if !path.exists() => fs::canonicalize(dir).map(|dir| dir.join(name)),
+            _ => fs::canonicalize(path),
+        };
+        if let Ok(target) = target {
+            if inner.files.iter().any(|f| f.id != id && f.path == target) {
+                return Err(file_error(path, "already open in another tab"));
+            }
+        }
+        fs::write(path, content).map_err(|e| file_error(path, e))?;
+        let path = fs::canonicalize(path).map_err(|e| file_error(path, e))?;
+        inner.last_dir = path.parent().map(Path::to_path_buf);
+        let file = inner.file(id)?;
+        file.path = path;
+        file.saved = content.to_string();
+        file.buffer = content.to_string();
+        self.keep_scratch(file);
+        let info = file.info();
+        self.persist(&inner);
+        Ok(info)
+    }
+
+    /// Renames the tab's file in place. Fails if the new name is taken.
+    pub fn rename(&self, id: &str, name: &str) -> Result<TabInfo, PotassiumError> {
+        let name = file_name(name)?;
+        let mut inner = self.lock();
+        let file = inner.file(id)?;
+        let target = file.path.with_file_name(&name);
+        if target != file.path {
+            // On case-insensitive filesystems `a.lua` -> `A.lua` finds
+            // the tab's own file; only another file blocks the rename.
+            if target.exists() && fs::canonicalize(&target).ok().as_ref() != Some(&file.path) {
+                return Err(file_error(&target, "a file with that name already exists"));
+            }
+            fs::rename(&file.path, &target).map_err(|e| file_error(&file.path, e))?;
+            file.path = target;
+        }
+        let info = file.info();
+        self.persist(&inner);
+        Ok(info)
+    }
+
+    /// Closes the tab. The file stays on disk; unsaved edits are dropped.
+    pub fn close(&self, id: &str) -> Result<(), PotassiumError> {
+        let mut inner = self.lock();
+        inner.file(id)?;
+        inner.files.retain(|f| f.id != id);
+        let _ = fs::remove_file(scratch_path(&self.scratch_dir, id));
+        if inner.active.as_deref() == Some(id) {
+            inner.active = inner.files.first().map(|f| f.id.clone());
+        }
+        self.persist(&inner);
+        Ok(())
+    }
+
+    pub fn set_execute_on_save(&self, id: &str, enabled: bool) -> Result<TabInfo, PotassiumError> {
+        let mut inner = self.lock();
+        let file = inner.file(id)?;
+        file.execute_on_save = enabled;
+        let info = file.info();
+        self.persist(&inner);
+        Ok(info)
+    }
+
+    /// Re-reads `path` if a tab has it open. Returns `None` when no tab
+    /// does, the file cannot be read, or it still matches what was last
+    /// saved (including our own writes).
+    pub fn sync_from_disk(&self, path: &Path) -> Option<FileChange> {
+        let path = fs::canonicalize(path).ok()?;
+        let mut inner = self.lock();
+        let file = inner.files.iter_mut().find(|f| f.path == path)?;
+        let text = fs::read_to_string(&path).ok()?;
+        if text == file.saved {
+            return None;
+        }
+        let clean = file.buffer == file.saved;
+        file.saved = text.clone();
+        if clean {
+            file.buffer = text.clone();
+        }
+        self.keep_scratch(file);
+        Some(FileChange { tab: file.info(), content: text, conflict: !clean })
+    }
+
+    pub fn set_active(&self, id: &str) -> Result<(), PotassiumError> {
+        let mut inner = self.lock();
+        inner.file(id)?;
+        inner.active = Some(id.to_string());
+        self.persist(&inner);
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use std::cell::RefCell;
+use std::fs;
+use std::time::Duration;
+
+use potassium_ui_lib::autoexec::{self, AutoexecSettings};
+use potassium_ui_lib::bundle;
+use potassium_ui_lib::error::PotassiumError;
+use potassium_ui_lib::mock::{MockBehavior, MockListener};
+use potassium_ui_lib::transport::Target;
+use tempfile::TempDir;
+
+const WAIT: Duration = Duration::from_secs(2);
+
+#[test]
+fn list_is_sorted_and_skips_other_files() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path();
+    for name in ["b.lua", "a.luau", "notes.md", "c.txt"] {
+        fs::write(dir.join(name), "print(1)").unwrap();
+    }
+    fs::create_dir(dir.join("nested.lua")).unwrap();
+    assert!(!AutoexecSettings::default().enabled);
+    let settings = AutoexecSettings { disabled: ["b.lua".to_string()].into(), ..Default::default() };
+
+    let files: Vec<(String, bool)> =
+        autoexec::list(dir, &settings).into_iter().map(|f| (f.name, f.enabled)).collect();
+    assert_eq!(
+        files,
+        [("a.luau".to_string(), true), ("b.lua".to_string(), false), ("c.txt".to_string(), true)]
+    );
+}
+
+#[tokio::test]
+async fn runs_enabled_scripts_in_order_and_keeps_going_after_a_failure() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path();
+    fs::write(dir.join("1-first.lua"), "print(1)").unwrap();
+    fs::write(dir.join("2-broken.lua"), "print(2)").unwrap();
+    fs::write(dir.join("3-skipped.lua"), "print(3)").unwrap();
+    fs::write(dir.join("4-empty.lua"), "  \n").unwrap();
+    fs::write(dir.join("5-last.lua"), "OpiumwareSetting fps 60").unwrap();
+    let settings = AutoexecSettings { disabled: ["3-skipped.lua".to_string()].into(), ..Default::default() };
+
+    let mock = MockListener::start("127.0.0.1", &[(0, MockBehavior::default())]).await.expect("bind loopback");
+    let port = mock.ports()[0].clone();
+    let client = mock.client();
+    let seen = RefCell::new(Vec::new());
+
+    let runs = autoexec::run(
+        dir,
+        &settings,
+        &port,
+        |path, code| {
+            let client = &client;
+            let target = Target::Port(port.clone());
+            async move {
+                if code.contains("print(2)") {
+                    return Err(PotassiumError::ConnectRefused { port: target.to_string() });
+                }
+                let script = bundle::prepare(&code, Some(&path), dir)?;
+                client.execute(&script.code, &target).await
+            }
+        },
+        |run| seen.borrow_mut().push(run.file.clone()),
+    )
+    .await;
+
+    let results: Vec<(&str, bool)> = runs.iter().map(|r| (r.file.as_str(), r.success)).collect();
+    assert_eq!(results, [("1-first.lua", true), ("2-broken.lua", false), ("5-last.lua", true)]);
+    assert!(runs.iter().all(|r| r.port == port));
+    assert_eq!(seen.into_inner(), ["1-first.lua", "2-broken.lua", "5-last.lua"]);
+
+    let received: Vec<String> = mock.wait_for(2, WAIT).await.into_iter().map(|r| r.script).collect();
+    assert_eq!(received, ["OpiumwareScript print(1)", "OpiumwareSetting fps 60"]);
+}
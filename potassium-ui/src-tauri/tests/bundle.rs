@@ -0,0 +1,157 @@
+use std::fs;
+use std::path::Path;
+
+use potassium_ui_lib::bundle::{bundle, prepare, Bundle, Script};
+use potassium_ui_lib::error::PotassiumError;
+use tempfile::TempDir;
+
+fn workspace(files: &[(&str, &str)]) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    for (path, content) in files {
+        let path = dir.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    dir
+}
+
+fn bundle_file(dir: &Path, entry: &str) -> Result<Bundle, PotassiumError> {
+    let path = dir.join(entry);
+    bundle(&fs::read_to_string(&path).unwrap(), Some(&path), dir)
+}
+
+#[test]
+fn inlines_modules_once_with_a_module_table() {
+    let dir = workspace(
+        &[
+            ("main.lua", "local util = require(\"./lib/util\")\nlocal again = require './lib/util.lua'\nprint(util.twice(2), again == util)\n"),
+            ("lib/util.lua", "local shared = require(\"../shared\")\nreturn { twice = function(n) return n * shared.factor end }\n"),
+            ("shared/init.luau", "return { factor = 2 }"),
+        ],
+    );
+    let result = bundle_file(dir.path(), "main.lua").unwrap();
+    let code = &result.code;
+
+    assert!(result.is_bundled());
+    assert_eq!(code.matches("__bundle_modules[\"lib/util.lua\"] = function(...)").count(), 1);
+    assert_eq!(code.matches("__bundle_modules[\"shared/init.luau\"] = function(...)").count(), 1);
+    assert!(code.contains("local shared = __bundle_require(\"shared/init.luau\")"));
+    assert!(code.ends_with(
+        "local util = __bundle_require(\"lib/util.lua\")\nlocal again = __bundle_require(\"lib/util.lua\")\nprint(util.twice(2), again == util)\n"
+    ));
+    let modules: Vec<&str> = result.segments.iter().map(|s| s.module.as_str()).collect();
+    assert_eq!(modules, ["shared/init.luau", "lib/util.lua", "main.lua"]);
+    assert_eq!(potassium_ui_lib::syntax::check(code), []);
+}
+
+#[test]
+fn bundle_lines_map_back_to_source_files() {
+    let dir = workspace(
+        &[("main.lua", "-- entry\nlocal m = require(\"./m\")\nm.run()\n"), ("m.lua", "local M = {}\nfunction M.run()\n\terror('boom')\nend\nreturn M\n")],
+    );
+    let result = bundle_file(dir.path(), "main.lua").unwrap();
+    let lines: Vec<&str> = result.code.lines().collect();
+
+    let boom = lines.iter().position(|l| l.contains("error('boom')")).unwrap() + 1;
+    let located = result.locate(boom).unwrap();
+    assert_eq!((located.module.as_str(), located.line), ("m.lua", 3));
+
+    let run = lines.iter().position(|l| *l == "m.run()").unwrap() + 1;
+    assert_eq!(result.locate(run).unwrap().line, 3);
+    assert_eq!(result.locate(1), None);
+}
+
+#[test]
+fn entry_lines_skip_the_header_and_blank_lines_after_it() {
+    let dir = workspace(&[("main.lua", "OpiumwareScript\n\nlocal m = require(\"./m\")\nm.run()\n"), ("m.lua", "return { run = print }\n")]);
+    let result = bundle_file(dir.path(), "main.lua").unwrap();
+    let lines: Vec<&str> = result.code.lines().collect();
+
+    assert_eq!(lines[0], "OpiumwareScript");
+    let run = lines.iter().position(|l| *l == "m.run()").unwrap() + 1;
+    let located = result.locate(run).unwrap();
+    assert_eq!((located.module.as_str(), located.line), ("main.lua", 4));
+    assert_eq!(potassium_ui_lib::syntax::check(&result.code), []);
+}
+
+#[test]
+fn prepared_scripts_get_the_header_and_annotate_listener_output() {
+    let dir = workspace(&[("main.lua", "local m = require(\"./m\")\nm.run()\n"), ("m.lua", "return { run = function()\n\terror('boom')\nend }\n")]);
+    let path = dir.path().join("main.lua");
+    let result = prepare(&fs::read_to_string(&path).unwrap(), Some(&path), dir.path()).unwrap();
+    assert!(result.code.starts_with("OpiumwareScript local __bundle_modules"), "{}", result.code);
+
+    let boom = result.code.lines().position(|l| l.contains("error('boom')")).unwrap() + 1;
+    let run = result.code.lines().position(|l| l == "m.run()").unwrap() + 1;
+    let output = format!("Script:{}: boom\nScript:{}: in main chunk\nat :1: and 12:x:", boom, run);
+    assert_eq!(
+        result.annotate(&output),
+        format!("Script:{} (m.lua:2): boom\nScript:{} (main.lua:2): in main chunk\nat :1: and 12:x:", boom, run)
+    );
+
+    let plain = prepare("  print(1)\n", None, dir.path()).unwrap();
+    assert_eq!(plain.code, "OpiumwareScript print(1)");
+    assert_eq!(plain.annotate(":1: oops"), ":1: oops");
+    assert_eq!(Bundle::raw("OpiumwareSetting x".into()).code, "OpiumwareSetting x");
+}
+
+#[test]
+fn cycles_and_missing_modules_are_errors() {
+    let dir = workspace(
+        &[
+            ("main.lua", "require(\"./a\")\n"),
+            ("a.lua", "return require(\"./b\")\n"),
+            ("b.lua", "return require(\"./a\")\n"),
+            ("missing.lua", "local x = 1\nrequire(\"./nope\")\n"),
+        ],
+    );
+    let err = bundle_file(dir.path(), "main.lua").unwrap_err();
+    assert_eq!(err.kind(), "bundle");
+    assert!(err.to_string().contains("require cycle: a.lua → b.lua → a.lua"), "{}", err);
+
+    let err = bundle_file(dir.path(), "missing.lua").unwrap_err();
+    assert!(err.to_string().contains("./nope (required at missing.lua:2) was not found"), "{}", err);
+}
+
+#[test]
+fn scripts_without_local_requires_are_unchanged() {
+    let dir = workspace(&[]);
+    for code in ["print(1)\n", "local m = require(game.ReplicatedStorage.M)\n", "OpiumwareScript print(1)", "print(("] {
+        let result = bundle(code, None, dir.path()).unwrap();
+        assert_eq!(result.code, code);
+        assert!(!result.is_bundled());
+    }
+}
+
+#[test]
+fn loading_checks_syntax_only_when_asked() {
+    let dir = workspace(&[]);
+    let source = || Script::Source { code: "print((", path: None, root: dir.path() };
+    assert_eq!(source().load(true).unwrap_err().kind(), "syntax");
+    assert!(source().load(false).is_ok());
+    assert_eq!(Script::Raw("print((").load(true).unwrap_err().kind(), "syntax");
+    assert_eq!(Script::Raw("print(1)").load(true).unwrap().code, "print(1)");
+}
+
+#[test]
+fn module_headers_are_not_inlined() {
+    let dir = workspace(
+        &[
+            ("main.lua", "local m = require(\"./m\")\nm.run()\n"),
+            ("m.lua", "OpiumwareScript\nlocal M = {}\nfunction M.run() error('boom') end\nreturn M\n"),
+            ("setting.lua", "require(\"./s\")\n"),
+            ("s.lua", "OpiumwareSetting x\n"),
+        ],
+    );
+    let result = bundle_file(dir.path(), "main.lua").unwrap();
+    assert!(!result.code.contains("OpiumwareScript"));
+    assert_eq!(potassium_ui_lib::syntax::check(&result.code), []);
+
+    let boom = result.code.lines().position(|l| l.contains("error('boom')")).unwrap() + 1;
+    let located = result.locate(boom).unwrap();
+    assert_eq!((located.module.as_str(), located.line), ("m.lua", 3));
+
+    let err = bundle_file(dir.path(), "setting.lua").unwrap_err();
+    assert_eq!(err.kind(), "bundle");
+    assert!(err.to_string().contains("s.lua is an OpiumwareSetting script"), "{}", err);
+}
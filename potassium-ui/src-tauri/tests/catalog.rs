@@ -0,0 +1,65 @@
+use potassium_ui_lib::catalog::{ApiCatalog, CatalogFormat, CATALOG_VERSION};
+use potassium_ui_lib::error::PotassiumError;
+
+#[test]
+fn legacy_list_converts_with_namespaces_and_docs() {
+    let text = "# executor API\ngetgenv\ndebug.getinfo - Info about a function\nrequest: HTTP request\n\ngetgenv\nnot valid!\n";
+    let catalog = ApiCatalog::from_legacy(text);
+
+    assert_eq!(catalog.version, CATALOG_VERSION);
+    let names: Vec<&str> = catalog.entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["debug.getinfo", "getgenv", "request"]);
+    assert_eq!(catalog.entries[0].namespace.as_deref(), Some("debug"));
+    assert_eq!(catalog.entries[0].description.as_deref(), Some("Info about a function"));
+    assert_eq!(catalog.lookup("request").unwrap().description.as_deref(), Some("HTTP request"));
+}
+
+#[test]
+fn builtin_catalog_documents_every_legacy_name() {
+    let catalog = ApiCatalog::builtin();
+    assert_eq!(catalog.version, CATALOG_VERSION);
+    let legacy = ApiCatalog::from_legacy(include_str!("../../src/functions.txt"));
+    let missing: Vec<&str> =
+        legacy.entries.iter().map(|e| e.name.as_str()).filter(|name| catalog.lookup(name).is_none()).collect();
+    assert_eq!(missing, Vec::<&str>::new());
+
+    let hook = catalog.lookup("replaceclosure").unwrap();
+    assert_eq!(hook.name, "hookfunction");
+    assert_eq!(hook.signature.as_deref(), Some("hookfunction(target: function, hook: function): function"));
+    assert_eq!(hook.params.len(), 2);
+    assert_eq!(hook.returns.as_deref(), Some("function"));
+}
+
+#[test]
+fn json_and_toml_round_trip() {
+    let json = r#"{
+        "version": 1,
+        "entries": [{
+            "name": "request",
+            "aliases": ["http_request"],
+            "signature": "request(options: table): table",
+            "params": [{ "name": "options", "type": "table" }],
+            "returns": "table",
+            "deprecated": true,
+            "examples": ["request({ Url = 'https://example.com' })"]
+        }]
+    }"#;
+    let catalog = ApiCatalog::parse(json, CatalogFormat::Json, "api-catalog.json").unwrap();
+    assert_eq!(catalog.lookup("http_request").unwrap().name, "request");
+    assert_eq!(catalog.entries[0].params[0].kind.as_deref(), Some("table"));
+
+    let toml = catalog.to_text(CatalogFormat::Toml);
+    assert_eq!(ApiCatalog::parse(&toml, CatalogFormat::Toml, "api-catalog.toml").unwrap(), catalog);
+    let json = catalog.to_text(CatalogFormat::Json);
+    assert_eq!(ApiCatalog::parse(&json, CatalogFormat::Json, "api-catalog.json").unwrap(), catalog);
+}
+
+#[test]
+fn newer_versions_and_duplicate_names_are_rejected() {
+    let future = format!(r#"{{ "version": {}, "entries": [] }}"#, CATALOG_VERSION + 1);
+    assert!(matches!(ApiCatalog::parse(&future, CatalogFormat::Json, "c"), Err(PotassiumError::File { .. })));
+
+    let duplicate = "version = 1\n[[entries]]\nname = \"request\"\n[[entries]]\nname = \"http_request\"\naliases = [\"request\"]\n";
+    let err = ApiCatalog::parse(duplicate, CatalogFormat::Toml, "c").unwrap_err();
+    assert!(err.to_string().contains("request is defined more than once"), "{}", err);
+}
@@ -0,0 +1,63 @@
+use std::io::Read;
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+use potassium_ui_lib::codec::{Codec, CodecKind, CodecSettings, Gzip, LengthPrefixed, Raw, Zlib};
+
+const SCRIPT: &str = "print('hello')\n-- ünïcödé\n";
+
+fn inflate(mut decoder: impl Read) -> String {
+    let mut out = String::new();
+    decoder.read_to_string(&mut out).unwrap();
+    out
+}
+
+/// Splits a length-prefixed payload, checking the prefix matches the body.
+fn unframe(payload: &[u8]) -> &[u8] {
+    let (len, body) = payload.split_at(4);
+    assert_eq!(u32::from_be_bytes(len.try_into().unwrap()) as usize, body.len());
+    body
+}
+
+#[test]
+fn zlib_round_trips() {
+    let script = SCRIPT.repeat(50);
+    for level in [0, 6, 9] {
+        let encoded = Zlib { level }.encode(script.as_bytes()).unwrap();
+        assert_eq!(inflate(ZlibDecoder::new(encoded.as_slice())), script);
+    }
+}
+
+#[test]
+fn gzip_round_trips() {
+    let encoded = Gzip { level: 6 }.encode(SCRIPT.as_bytes()).unwrap();
+    assert_eq!(&encoded[..2], [0x1f, 0x8b]);
+    assert_eq!(inflate(GzDecoder::new(encoded.as_slice())), SCRIPT);
+}
+
+#[test]
+fn raw_is_the_script_bytes() {
+    assert_eq!(Raw.encode(SCRIPT.as_bytes()).unwrap(), SCRIPT.as_bytes());
+    assert_eq!(Raw.encode(b"").unwrap(), b"");
+}
+
+#[test]
+fn length_prefixed_frames_each_codec() {
+    let zlib = LengthPrefixed(Zlib::default()).encode(SCRIPT.as_bytes()).unwrap();
+    assert_eq!(inflate(ZlibDecoder::new(unframe(&zlib))), SCRIPT);
+
+    let gzip = LengthPrefixed(Gzip { level: 1 }).encode(SCRIPT.as_bytes()).unwrap();
+    assert_eq!(inflate(GzDecoder::new(unframe(&gzip))), SCRIPT);
+
+    let raw = LengthPrefixed(Raw).encode(SCRIPT.as_bytes()).unwrap();
+    assert_eq!(unframe(&raw), SCRIPT.as_bytes());
+}
+
+#[test]
+fn settings_build_the_named_codec() {
+    let names: Vec<&str> = [CodecKind::Zlib, CodecKind::Gzip, CodecKind::Raw]
+        .into_iter()
+        .flat_map(|codec| [false, true].map(|length_prefix| CodecSettings { codec, level: 6, length_prefix }))
+        .map(|settings| settings.build().name())
+        .collect();
+    assert_eq!(names, ["zlib", "zlib+length", "gzip", "gzip+length", "raw", "raw+length"]);
+}
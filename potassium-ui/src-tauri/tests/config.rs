@@ -0,0 +1,49 @@
+use std::fs;
+
+use potassium_ui_lib::config::{Config, ConfigStore, PortSpec};
+use tempfile::TempDir;
+
+#[test]
+fn port_lists_reject_duplicates() {
+    let mut config = Config { ports: PortSpec::List(vec![8392, 8393, 8392]), ..Default::default() };
+    assert_eq!(config.validate().unwrap_err().to_string(), "Invalid config: Port 8392 is listed more than once");
+
+    config.ports = PortSpec::List(vec![8392, 8393]);
+    assert_eq!(config.validate(), Ok(()));
+    config.ports = PortSpec::Range { start: 8392, end: 8397 };
+    assert_eq!(config.validate(), Ok(()));
+    config.host = "::1".into();
+    assert_eq!(config.validate(), Ok(()));
+}
+
+#[test]
+fn read_rejects_files_that_do_not_parse() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("config.json");
+
+    fs::write(&path, r#"{ "ports": [9000, 9001] }"#).unwrap();
+    assert_eq!(Config::read(&path).unwrap().port_list(), ["9000", "9001"]);
+
+    fs::write(&path, r#"{ "ports": "lots" }"#).unwrap();
+    assert!(Config::read(&path).is_err());
+    fs::write(&path, r#"{ "ports": [] }"#).unwrap();
+    assert!(Config::read(&path).is_err());
+    assert!(Config::read(&tmp.path().join("missing.json")).is_err());
+}
+
+#[test]
+fn invalid_files_are_backed_up_before_anything_overwrites_them() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("config.json");
+    let broken = r#"{ "ports": [9000, 9000] }"#;
+    fs::write(&path, broken).unwrap();
+
+    let store = ConfigStore::load(path.clone());
+    assert_eq!(store.get(), Config::default());
+    assert!(!path.exists());
+    assert_eq!(fs::read_to_string(tmp.path().join("config.json.bak")).unwrap(), broken);
+
+    store.set(Config::default()).unwrap();
+    assert!(path.exists());
+    assert_eq!(fs::read_to_string(tmp.path().join("config.json.bak")).unwrap(), broken);
+}
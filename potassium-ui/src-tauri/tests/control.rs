@@ -0,0 +1,217 @@
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use futures::{FutureExt, StreamExt};
+use potassium_ui_lib::bundle::Script;
+use potassium_ui_lib::control::{ControlHandler, ControlServer, ControlStatus};
+use potassium_ui_lib::error::PotassiumError;
+use potassium_ui_lib::mock::{MockBehavior, MockListener};
+use potassium_ui_lib::report::ExecutionReport;
+use potassium_ui_lib::session::{AttachedPort, Session};
+use potassium_ui_lib::transport::{Client, Target};
+use serde_json::Value;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const TOKEN: &str = "test-token";
+const WAIT: Duration = Duration::from_secs(2);
+
+/// Executes straight through a client pointed at the mock, checking the
+/// syntax of non-raw scripts like the app with `blockOnSyntaxError` on.
+struct MockControl {
+    client: Client,
+    session: Arc<Session>,
+}
+
+impl ControlHandler for MockControl {
+    fn status(&self) -> BoxFuture<'static, Result<ControlStatus, PotassiumError>> {
+        let session = self.session.snapshot();
+        async move {
+            Ok(ControlStatus { backend: "opiumware".into(), session, instances: Vec::new(), connections: Vec::new() })
+        }
+        .boxed()
+    }
+
+    fn attach(&self, port: Option<String>) -> BoxFuture<'static, Result<AttachedPort, PotassiumError>> {
+        let (client, session) = (self.client.clone(), self.session.clone());
+        async move {
+            let port = match port {
+                Some(port) => port,
+                None => client.attach_any().await?,
+            };
+            Ok(session.attach(&port).0)
+        }
+        .boxed()
+    }
+
+    fn execute(
+        &self,
+        code: String,
+        port: Option<String>,
+        raw: bool,
+    ) -> BoxFuture<'static, Result<ExecutionReport, PotassiumError>> {
+        let client = self.client.clone();
+        let code = if raw { Ok(code) } else { Script::Source { code: &code, path: None, root: Path::new(".") }.load(true).map(|b| b.code) };
+        async move { client.execute(&code?, &Target::parse(&port.unwrap_or_else(|| "ALL".into()))).await }.boxed()
+    }
+}
+
+async fn start(mock: &MockListener) -> (ControlServer, SocketAddr) {
+    start_with(mock, TOKEN, &[]).await
+}
+
+async fn start_with(mock: &MockListener, token: &str, origins: &[&str]) -> (ControlServer, SocketAddr) {
+    let server = ControlServer::new();
+    let handler = Arc::new(MockControl { client: mock.client(), session: Arc::new(Session::new()) });
+    let origins = origins.iter().map(|o| o.to_string()).collect();
+    let addr = server.start(0, token.into(), origins, handler).await.expect("bind control API");
+    (server, addr)
+}
+
+/// Minimal HTTP/1.1 request; returns the status code and JSON body.
+async fn request(addr: SocketAddr, method: &str, path: &str, token: Option<&str>, body: &str) -> (u16, Value) {
+    let auth = token.map(|t| format!("Authorization: Bearer {}\r\n", t)).unwrap_or_default();
+    request_with(addr, method, path, &auth, body).await
+}
+
+/// Like `request`, with `headers` (each ending in CRLF) added as-is.
+async fn request_with(addr: SocketAddr, method: &str, path: &str, headers: &str, body: &str) -> (u16, Value) {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let head = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n{}Content-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+        method,
+        path,
+        addr,
+        headers,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await.unwrap();
+    stream.write_all(body.as_bytes()).await.unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    let status = response.split(' ').nth(1).unwrap().parse().unwrap();
+    let json = response.split_once("\r\n\r\n").map(|(_, b)| b).unwrap_or_default();
+    (status, serde_json::from_str(json).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn requests_without_the_token_are_rejected() {
+    let mock = MockListener::start("127.0.0.1", &[(0, MockBehavior::default())]).await.unwrap();
+    let (_server, addr) = start(&mock).await;
+
+    let (status, body) = request(addr, "GET", "/status", None, "").await;
+    assert_eq!(status, 401);
+    assert_eq!(body["kind"], "unauthorized");
+
+    let (status, _) = request(addr, "POST", "/execute", Some("wrong-token"), r#"{"code":"print(1)"}"#).await;
+    assert_eq!(status, 401);
+    assert!(mock.received().is_empty());
+
+    let (status, body) = request(addr, "GET", &format!("/status?token={}", TOKEN), None, "").await;
+    assert_eq!(status, 200);
+    assert_eq!(body["backend"], "opiumware");
+}
+
+#[tokio::test]
+async fn query_tokens_are_percent_decoded() {
+    let mock = MockListener::start("127.0.0.1", &[(0, MockBehavior::default())]).await.unwrap();
+    let (_server, addr) = start_with(&mock, "a+b/c=d&e", &[]).await;
+
+    let (status, _) = request(addr, "GET", "/status?token=a%2Bb%2Fc%3Dd%26e", None, "").await;
+    assert_eq!(status, 200);
+    let (status, _) = request(addr, "GET", "/status?token=a+b/c=d&e", None, "").await;
+    assert_eq!(status, 401);
+}
+
+#[tokio::test]
+async fn browser_origins_must_be_allowlisted() {
+    let mock = MockListener::start("127.0.0.1", &[(0, MockBehavior::default())]).await.unwrap();
+    let (_server, addr) = start_with(&mock, TOKEN, &["vscode-webview://potassium"]).await;
+    let auth = format!("Authorization: Bearer {}\r\n", TOKEN);
+
+    let evil = format!("{}Origin: https://example.com\r\n", auth);
+    let (status, body) = request_with(addr, "GET", "/status", &evil, "").await;
+    assert_eq!(status, 403);
+    assert_eq!(body["kind"], "originNotAllowed");
+
+    let allowed = format!("{}Origin: vscode-webview://potassium\r\n", auth);
+    assert_eq!(request_with(addr, "GET", "/status", &allowed, "").await.0, 200);
+    assert_eq!(request_with(addr, "GET", "/status", &auth, "").await.0, 200);
+}
+
+#[tokio::test]
+async fn execute_and_attach_go_through_the_handler() {
+    let mock = MockListener::start("127.0.0.1", &[(0, MockBehavior::refuse()), (0, MockBehavior::default())])
+        .await
+        .unwrap();
+    let live = mock.ports()[1].clone();
+    let (_server, addr) = start(&mock).await;
+
+    let (status, body) = request(addr, "POST", "/attach", Some(TOKEN), "{}").await;
+    assert_eq!(status, 200);
+    assert_eq!(body["port"], live.as_str());
+
+    let exec = format!(r#"{{"code":"print(1)","port":"{}"}}"#, live);
+    let (status, body) = request(addr, "POST", "/execute", Some(TOKEN), &exec).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["ports"][0]["connected"], true);
+
+    let raw = format!(r#"{{"code":"OpiumwareSetting x","port":"{}","raw":true}}"#, live);
+    request(addr, "POST", "/execute", Some(TOKEN), &raw).await;
+
+    let scripts: Vec<String> = mock.wait_for(2, WAIT).await.into_iter().map(|r| r.script).collect();
+    assert_eq!(scripts, ["OpiumwareScript print(1)", "OpiumwareSetting x"]);
+
+    let (status, body) = request(addr, "POST", "/execute", Some(TOKEN), r#"{"code":"print(1)","port":"0"}"#).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["kind"], "invalidPort");
+
+    let broken = format!(r#"{{"code":"print((","port":"{}"}}"#, live);
+    let (status, body) = request(addr, "POST", "/execute", Some(TOKEN), &broken).await;
+    assert_eq!(status, 422);
+    assert_eq!(body["kind"], "syntax");
+}
+
+#[tokio::test]
+async fn websocket_streams_published_events_until_stopped() {
+    let mock = MockListener::start("127.0.0.1", &[(0, MockBehavior::default())]).await.unwrap();
+    let (server, addr) = start(&mock).await;
+
+    let url = format!("ws://{}/events?token={}", addr, TOKEN);
+    let (mut socket, _) = tokio_tungstenite::connect_async(url).await.expect("websocket");
+
+    // The subscription exists once the upgrade is done; publish until seen.
+    let payload = serde_json::json!({ "port": "8392", "up": true });
+    let message = tokio::time::timeout(WAIT, async {
+        loop {
+            server.publish("instance-up", &payload);
+            if let Ok(Some(Ok(message))) = tokio::time::timeout(Duration::from_millis(50), socket.next()).await {
+                return message;
+            }
+        }
+    })
+    .await
+    .expect("event");
+    let event: Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+    assert_eq!(event["event"], "instance-up");
+    assert_eq!(event["payload"], payload);
+
+    server.stop().await;
+    assert!(server.address().is_none());
+    let closed = tokio::time::timeout(WAIT, async {
+        while let Some(Ok(message)) = socket.next().await {
+            if message.is_close() {
+                break;
+            }
+        }
+    })
+    .await;
+    assert!(closed.is_ok());
+
+    let refused = tokio_tungstenite::connect_async(format!("ws://{}/events?token={}", addr, TOKEN)).await;
+    assert!(refused.is_err());
+}
@@ -0,0 +1,53 @@
+use potassium_ui_lib::config::Config;
+use potassium_ui_lib::error::PotassiumError;
+use potassium_ui_lib::format::{format, CallParentheses, FormatSettings, IndentType, LineRange, QuoteStyle};
+
+const MESSY: &str = "local t={1,2}\nif t then\n      print( 'hi' )\nend\n";
+
+#[test]
+fn defaults_match_stylua() {
+    assert_eq!(format(MESSY, &FormatSettings::default(), None).unwrap(), "local t = { 1, 2 }\nif t then\n\tprint(\"hi\")\nend\n");
+}
+
+#[test]
+fn settings_change_the_output() {
+    let settings = FormatSettings {
+        indent_type: IndentType::Spaces,
+        indent_width: 2,
+        quote_style: QuoteStyle::ForceSingle,
+        call_parentheses: CallParentheses::NoSingleString,
+        ..Default::default()
+    };
+    assert_eq!(format(MESSY, &settings, None).unwrap(), "local t = { 1, 2 }\nif t then\n  print 'hi'\nend\n");
+
+    let settings: FormatSettings =
+        serde_json::from_str(r#"{ "indentType": "Spaces", "quoteStyle": "AutoPreferSingle", "columnWidth": 80 }"#).unwrap();
+    assert_eq!((settings.indent_width, settings.column_width), (4, 80));
+}
+
+#[test]
+fn selection_formats_only_those_lines() {
+    let code = "local a={1}\nlocal b={2}\nlocal c={3}\n";
+    let lines = LineRange { start_line: 2, end_line: 2 };
+    assert_eq!(format(code, &FormatSettings::default(), Some(lines)).unwrap(), "local a={1}\nlocal b = { 2 }\nlocal c={3}\n");
+}
+
+#[test]
+fn headers_are_kept_and_broken_scripts_rejected() {
+    let settings = FormatSettings::default();
+    assert_eq!(format("OpiumwareScript print( 1 )", &settings, None).unwrap(), "OpiumwareScript print(1)\n");
+    assert_eq!(format("OpiumwareSetting fps 60", &settings, None).unwrap(), "OpiumwareSetting fps 60");
+    assert!(matches!(format("print(", &settings, None), Err(PotassiumError::Syntax { .. })));
+}
+
+#[test]
+fn config_validates_format_settings() {
+    let mut config = Config::default();
+    config.format.indent_width = 0;
+    assert!(config.validate().is_err());
+    config.format.indent_width = 2;
+    config.format.column_width = 10;
+    assert!(config.validate().is_err());
+    config.format.column_width = 100;
+    assert_eq!(config.validate(), Ok(()));
+}
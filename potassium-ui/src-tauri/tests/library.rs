@@ -0,0 +1,76 @@
+use std::fs;
+
+use potassium_ui_lib::error::PotassiumError;
+use potassium_ui_lib::library::{Library, LIBRARY_FILE};
+use tempfile::TempDir;
+
+/// A fresh library root with a few scripts in nested folders.
+fn library() -> (TempDir, Library) {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    fs::create_dir_all(root.join("Games/Arsenal")).unwrap();
+    fs::write(root.join("esp.lua"), "-- draw boxes\nlocal Players = game:GetService('Players')\n").unwrap();
+    fs::write(root.join("Games/Arsenal/aim.lua"), "print('aim')\n").unwrap();
+    fs::write(root.join("Games/notes.md"), "not a script").unwrap();
+    let library = Library::load(root.to_path_buf());
+    (tmp, library)
+}
+
+#[test]
+fn tree_nests_folders_and_skips_other_files() {
+    let (_root, library) = library();
+    let tree = library.tree().unwrap();
+
+    assert_eq!(tree.path, "");
+    assert_eq!(tree.scripts.iter().map(|s| s.path.as_str()).collect::<Vec<_>>(), ["esp.lua"]);
+    let games = &tree.folders[0];
+    assert_eq!((games.name.as_str(), games.scripts.len()), ("Games", 0));
+    assert_eq!(games.folders[0].scripts[0].path, "Games/Arsenal/aim.lua");
+}
+
+#[test]
+fn tags_and_favorites_live_in_the_sidecar() {
+    let (root, library) = library();
+    let script = library.set_tags("Games/Arsenal/aim.lua", &[" combat ".into(), "".into(), "combat".into()]).unwrap();
+    assert_eq!(script.tags, ["combat"]);
+    library.set_favorite("esp.lua", true).unwrap();
+    assert!(root.path().join(LIBRARY_FILE).is_file());
+
+    let reloaded = Library::load(root.path().to_path_buf()).tree().unwrap();
+    assert!(reloaded.scripts[0].favorite);
+    assert_eq!(reloaded.folders[0].folders[0].scripts[0].tags, ["combat"]);
+
+    assert!(matches!(library.set_favorite("../esp.lua", true), Err(PotassiumError::File { .. })));
+    assert!(matches!(library.read("missing.lua"), Err(PotassiumError::File { .. })));
+}
+
+#[test]
+fn search_matches_names_tags_and_contents() {
+    let (_root, library) = library();
+    library.set_tags("Games/Arsenal/aim.lua", &["combat".into()]).unwrap();
+
+    let hits = library.search("players");
+    assert_eq!(hits.len(), 1);
+    assert_eq!((hits[0].script.path.as_str(), hits[0].line), ("esp.lua", Some(2)));
+    assert_eq!(hits[0].preview.as_deref(), Some("local Players = game:GetService('Players')"));
+
+    let paths = |q: &str| library.search(q).into_iter().map(|h| h.script.path).collect::<Vec<_>>();
+    assert_eq!(paths("#combat"), ["Games/Arsenal/aim.lua"]);
+    assert_eq!(paths("arsenal print"), ["Games/Arsenal/aim.lua"]);
+    assert!(paths("#combat boxes").is_empty());
+
+    library.set_favorite("Games/Arsenal/aim.lua", true).unwrap();
+    assert_eq!(paths("lua"), ["Games/Arsenal/aim.lua", "esp.lua"]);
+}
+
+#[test]
+fn add_creates_folders_and_never_overwrites() {
+    let (root, library) = library();
+    let script = library.add("Hubs/New", "hub", "print('hub')").unwrap();
+    assert_eq!(script.path, "Hubs/New/hub.lua");
+    assert_eq!(fs::read_to_string(root.path().join("Hubs/New/hub.lua")).unwrap(), "print('hub')");
+
+    assert!(matches!(library.add("", "esp.lua", "x"), Err(PotassiumError::File { .. })));
+    assert!(matches!(library.add("..", "up", "x"), Err(PotassiumError::File { .. })));
+    assert_eq!(library.read("esp.lua").unwrap().lines().next(), Some("-- draw boxes"));
+}
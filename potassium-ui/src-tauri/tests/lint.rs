@@ -0,0 +1,77 @@
+use std::fs;
+
+use potassium_ui_lib::catalog::ApiCatalog;
+use potassium_ui_lib::lint::{lint, LintConfig, Rule, RuleLevel, LINT_FILE};
+use potassium_ui_lib::syntax::Severity;
+use tempfile::TempDir;
+
+fn rules(code: &str, config: &LintConfig) -> Vec<(usize, String)> {
+    lint(code, &ApiCatalog::builtin(), config).into_iter().map(|d| (d.line, d.rule.unwrap())).collect()
+}
+
+#[test]
+fn clean_script_has_no_findings() {
+    let code = "\
+local Players = game:GetService('Players')
+local function greet(player, _unused)
+\tprint('hi', player.Name)
+end
+for _, p in ipairs(Players:GetPlayers()) do greet(p) end
+local env = getgenv()
+env.loaded = true
+local n = 0
+repeat local done = n > 3; n += 1 until done
+counter = 1
+function bump() counter += 1 end
+bump()
+";
+    assert_eq!(rules(code, &LintConfig::default()), []);
+}
+
+#[test]
+fn reports_each_rule_with_positions() {
+    let code = "local a = 1\nlocal b = 2\nif true then\n  local a = 3\n  print(a, b, missing)\nend\n";
+    let diagnostics = lint(code, &ApiCatalog::builtin(), &LintConfig::default());
+    let found: Vec<(usize, usize, &str)> =
+        diagnostics.iter().map(|d| (d.line, d.column, d.rule.as_deref().unwrap())).collect();
+    assert_eq!(found, [(1, 7, "unused-variable"), (4, 9, "shadowed-local"), (5, 15, "undefined-global")]);
+    assert!(diagnostics.iter().all(|d| d.severity == Severity::Warning));
+    assert_eq!(diagnostics[2].message, "`missing` is not defined");
+    assert_eq!(diagnostics[2].end_column, 22);
+}
+
+#[test]
+fn config_sets_levels_and_allowlist() {
+    let code = "local x = 1\nprint(Rayfield, Fluent)\n";
+    let mut config = LintConfig::default();
+    config.rules.insert(Rule::UnusedVariable, RuleLevel::Off);
+    config.rules.insert(Rule::UndefinedGlobal, RuleLevel::Error);
+    config.globals.insert("Fluent".into());
+
+    let diagnostics = lint(code, &ApiCatalog::builtin(), &config);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "`Rayfield` is not defined");
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+}
+
+#[test]
+fn config_loads_from_the_workspace() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path();
+    assert_eq!(LintConfig::load(dir).unwrap(), LintConfig::default());
+
+    fs::write(dir.join(LINT_FILE), r#"{ "rules": { "shadowed-local": "off" }, "globals": ["Drawing"] }"#).unwrap();
+    let config = LintConfig::load(dir).unwrap();
+    assert_eq!(config.level(Rule::ShadowedLocal), RuleLevel::Off);
+    assert_eq!(config.level(Rule::UnusedVariable), RuleLevel::Warning);
+    assert!(config.globals.contains("Drawing"));
+
+    fs::write(dir.join(LINT_FILE), r#"{ "rules": { "no-such-rule": "off" } }"#).unwrap();
+    assert!(LintConfig::load(dir).is_err());
+}
+
+#[test]
+fn broken_scripts_are_left_to_the_syntax_check() {
+    assert_eq!(rules("local x = \nprint(", &LintConfig::default()), []);
+    assert_eq!(rules("OpiumwareSetting fps 60", &LintConfig::default()), []);
+}
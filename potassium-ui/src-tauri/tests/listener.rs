@@ -0,0 +1,175 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use potassium_ui_lib::codec::{CodecKind, CodecSettings};
+use potassium_ui_lib::error::PotassiumError;
+use potassium_ui_lib::mock::{MockBehavior, MockListener};
+use potassium_ui_lib::pool::{ConnectionPool, ConnectionState};
+use potassium_ui_lib::reply::ReplyOptions;
+use potassium_ui_lib::transport::{Client, Target};
+
+const WAIT: Duration = Duration::from_secs(2);
+
+fn replies(inflate: bool) -> ReplyOptions {
+    ReplyOptions { enabled: true, inflate, ..ReplyOptions::default() }
+}
+
+#[tokio::test]
+async fn scripted_reply_lands_in_the_report() {
+    let behavior = MockBehavior { reply: Some("ok from mock".into()), compress_reply: true, ..MockBehavior::default() };
+    let mock = MockListener::start("127.0.0.1", &[(0, behavior)]).await.unwrap();
+    let port = mock.ports()[0].clone();
+
+    let client = mock.client().with_reply(replies(true));
+    let report = client.execute("print(1)", &Target::Port(port)).await.unwrap();
+
+    let reply = report.ports[0].reply.as_ref().expect("reply");
+    assert_eq!(reply.text, "ok from mock");
+    assert!(reply.inflated);
+    assert!(!reply.truncated);
+    assert!(!reply.timed_out);
+}
+
+#[tokio::test]
+async fn reply_is_cut_at_max_bytes() {
+    let mock = MockListener::start("127.0.0.1", &[(0, MockBehavior::reply("0123456789"))]).await.unwrap();
+    let port = mock.ports()[0].clone();
+
+    let options = ReplyOptions { max_bytes: 4, ..replies(false) };
+    let report = mock.client().with_reply(options).execute("print(1)", &Target::Port(port)).await.unwrap();
+
+    let reply = report.ports[0].reply.as_ref().expect("reply");
+    assert_eq!(reply.text, "0123");
+    assert!(reply.truncated);
+}
+
+#[tokio::test]
+async fn slow_listener_times_out_the_reply_not_the_send() {
+    let mock = MockListener::start("127.0.0.1", &[(0, MockBehavior::slow_accept(Duration::from_millis(500)))])
+        .await
+        .unwrap();
+    let port = mock.ports()[0].clone();
+
+    let options = ReplyOptions { timeout_ms: 50, ..replies(false) };
+    let report = mock.client().with_reply(options).execute("print(1)", &Target::Port(port)).await.unwrap();
+
+    assert!(report.is_success());
+    assert!(report.ports[0].reply.is_none());
+    assert!(report.ports[0].reply_error.is_none());
+    assert_eq!(mock.wait_for(1, WAIT).await.len(), 1);
+}
+
+#[tokio::test]
+async fn reset_mid_stream_is_a_read_failure() {
+    let mock = MockListener::start("127.0.0.1", &[(0, MockBehavior::reset_after(1))]).await.unwrap();
+    let port = mock.ports()[0].clone();
+
+    let report = mock.client().with_reply(replies(false)).execute("print(1)", &Target::Port(port.clone())).await.unwrap();
+
+    assert!(report.ports[0].connected);
+    match &report.ports[0].reply_error {
+        Some(PotassiumError::ReadFailed { port: p, .. }) => assert_eq!(p, &port),
+        other => panic!("expected ReadFailed, got {:?}", other),
+    }
+    assert!(mock.received().is_empty());
+}
+
+#[tokio::test]
+async fn raw_codec_profile_is_received_as_is() {
+    let mock = MockListener::start("127.0.0.1", &[(0, MockBehavior::default()), (0, MockBehavior::default())])
+        .await
+        .unwrap();
+    let raw_port = mock.ports()[1].clone();
+    let raw = CodecSettings { codec: CodecKind::Raw, ..CodecSettings::default() };
+
+    let client = mock.client().with_port_codec(raw_port.clone(), raw.build());
+    client.execute("print(2)", &Target::All).await.unwrap();
+
+    let mut received = mock.wait_for(2, WAIT).await;
+    received.sort_by(|a, b| a.port.cmp(&b.port));
+    assert_eq!(received.len(), 2);
+    assert!(received.iter().all(|r| r.script == "print(2)"));
+    let by_raw = received.iter().find(|r| r.port == raw_port).unwrap();
+    assert!(!by_raw.inflated);
+}
+
+#[tokio::test]
+async fn pooled_connection_carries_several_scripts() {
+    let mock = MockListener::start("127.0.0.1", &[(0, MockBehavior::default())]).await.unwrap();
+    let port = mock.ports()[0].clone();
+
+    let states = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = states.clone();
+    let pool = Arc::new(ConnectionPool::new(move |e| seen.lock().unwrap().push(e.state)));
+    pool.open(&mock.client(), &port);
+    for _ in 0..50 {
+        if pool.states().first().is_some_and(|s| s.state == ConnectionState::Connected) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let client = mock.client().with_pool(pool.clone());
+    for i in 0..3 {
+        let report = client.execute(&format!("print({})", i), &Target::Port(port.clone())).await.unwrap();
+        assert!(report.is_success());
+    }
+
+    let received = mock.wait_for(3, WAIT).await;
+    let scripts: Vec<&str> = received.iter().map(|r| r.script.as_str()).collect();
+    assert_eq!(scripts, ["print(0)", "print(1)", "print(2)"]);
+
+    pool.close(&port);
+    assert_eq!(
+        *states.lock().unwrap(),
+        [ConnectionState::Connecting, ConnectionState::Connected, ConnectionState::Disconnected]
+    );
+}
+
+#[tokio::test]
+async fn stalled_pooled_write_times_out_and_reconnects() {
+    // Accepts and never reads, so a large enough write fills the buffers.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+    tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok((stream, _)) = listener.accept().await {
+            held.push(stream);
+        }
+    });
+
+    let states = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = states.clone();
+    let pool = ConnectionPool::new(move |e| seen.lock().unwrap().push(e.state));
+    pool.open(&Client::new("127.0.0.1", vec![port.clone()]), &port);
+    for _ in 0..50 {
+        if pool.states().first().is_some_and(|s| s.state == ConnectionState::Connected) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let payload = vec![0u8; 64 * 1024 * 1024];
+    match pool.send(&port, &payload, Duration::from_millis(200)).await {
+        Some(Err(PotassiumError::WriteFailed { message, .. })) => assert_eq!(message, "write timed out"),
+        other => panic!("expected a timed-out write, got {:?}", other.map(|r| r.is_ok())),
+    }
+
+    for _ in 0..100 {
+        if states.lock().unwrap().iter().filter(|s| **s == ConnectionState::Connected).count() == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    pool.close(&port);
+    assert_eq!(
+        states.lock().unwrap()[..5],
+        [
+            ConnectionState::Connecting,
+            ConnectionState::Connected,
+            ConnectionState::Disconnected,
+            ConnectionState::Reconnecting,
+            ConnectionState::Connected,
+        ]
+    );
+}
@@ -0,0 +1,44 @@
+use potassium_ui_lib::error::PotassiumError;
+use potassium_ui_lib::syntax::{check, ensure_valid, has_header};
+
+#[test]
+fn valid_luau_has_no_diagnostics() {
+    let code = "local t: { number } = {1, 2}\nfor _, v in t do\n\tif v > 1 then continue end\n\tprint(`v = {v}`)\nend\n";
+    assert_eq!(check(code), []);
+    assert_eq!(ensure_valid(code), Ok(()));
+}
+
+#[test]
+fn errors_carry_one_based_positions() {
+    let diagnostics = check("local x = 1\nprint(");
+    assert_eq!(diagnostics.len(), 1);
+    let d = &diagnostics[0];
+    assert_eq!((d.line, d.column), (2, 6));
+    assert!(d.end_column > d.column);
+    assert!(d.message.contains(')'), "{}", d.message);
+
+    let unclosed = &check("x = 'abc")[0];
+    assert_eq!((unclosed.line, unclosed.column, unclosed.message.as_str()), (1, 5, "unclosed string"));
+}
+
+#[test]
+fn opiumware_headers_are_not_lua() {
+    assert_eq!(check("OpiumwareScript print(1)"), []);
+    assert_eq!(check("OpiumwareSetting fps 60"), []);
+    assert_eq!((check("OpiumwareScript print(")[0].line, check("OpiumwareScript print(")[0].column), (1, 22));
+}
+
+#[test]
+fn headers_must_be_whole_words() {
+    assert!(has_header("\n  OpiumwareScript\nprint(1)"));
+    assert!(has_header("OpiumwareSetting fps 60"));
+    assert!(!has_header("OpiumwareScripts()"));
+    assert!(!has_header("print('OpiumwareScript')"));
+}
+
+#[test]
+fn ensure_valid_reports_the_first_error() {
+    let err = ensure_valid("if x then\n  print(1\nend").unwrap_err();
+    assert!(matches!(err, PotassiumError::Syntax { line: 2, column: 8, .. }), "{:?}", err);
+    assert_eq!(err.kind(), "syntax");
+}
@@ -0,0 +1,171 @@
+use std::io::Read;
+use std::time::Duration;
+
+use flate2::read::ZlibDecoder;
+use potassium_ui_lib::backend::Opiumware;
+use potassium_ui_lib::error::PotassiumError;
+use potassium_ui_lib::mock::{MockBehavior, MockListener};
+use potassium_ui_lib::transport::{compress_data, Client, Target};
+
+const WAIT: Duration = Duration::from_secs(2);
+
+async fn listeners(behaviors: Vec<MockBehavior>) -> MockListener {
+    let specs: Vec<(u16, MockBehavior)> = behaviors.into_iter().map(|b| (0, b)).collect();
+    MockListener::start("127.0.0.1", &specs).await.expect("bind loopback")
+}
+
+#[test]
+fn compress_data_round_trips() {
+    let script = "print('hello')\n-- ünïcödé\n".repeat(50);
+    let compressed = compress_data(script.as_bytes()).unwrap();
+    assert!(compressed.len() < script.len());
+
+    let mut inflated = String::new();
+    ZlibDecoder::new(compressed.as_slice()).read_to_string(&mut inflated).unwrap();
+    assert_eq!(inflated, script);
+}
+
+#[test]
+fn packet_adds_the_script_header_once() {
+    assert_eq!(Opiumware::packet("  print(1)\n"), "OpiumwareScript print(1)");
+    assert_eq!(Opiumware::packet("OpiumwareScript print(1)"), "OpiumwareScript print(1)");
+    assert_eq!(Opiumware::packet("OpiumwareSetting fps 60"), "OpiumwareSetting fps 60");
+    assert_eq!(Opiumware::packet("OpiumwareScripts()"), "OpiumwareScript OpiumwareScripts()");
+    assert_eq!(Opiumware::packet(" \n"), "");
+}
+
+#[tokio::test]
+async fn single_port_delivers_only_to_that_port() {
+    let mock = listeners(vec![MockBehavior::default(), MockBehavior::default()]).await;
+    let port = mock.ports()[1].clone();
+
+    let report = mock.client().execute("print(1)", &Target::Port(port.clone())).await.unwrap();
+
+    assert!(!report.probe);
+    assert_eq!(report.success_ports(), vec![port.as_str()]);
+    assert_eq!(report.message(), format!("Successfully connected to Opiumware on port: {}", port));
+
+    let received = mock.wait_for(1, WAIT).await;
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].port, port);
+    assert_eq!(received[0].script, "print(1)");
+    assert!(received[0].inflated);
+}
+
+#[tokio::test]
+async fn ipv6_hosts_connect() {
+    let mock = MockListener::start("::1", &[(0, MockBehavior::default())]).await.expect("bind IPv6 loopback");
+    let port = mock.ports()[0].clone();
+
+    let report = mock.client().execute("print(6)", &Target::Port(port.clone())).await.unwrap();
+    assert_eq!(report.success_ports(), vec![port.as_str()]);
+    assert_eq!(mock.wait_for(1, WAIT).await[0].script, "print(6)");
+
+    let err = Client::new("localhost", vec![port.clone()]).probe(&port).await.unwrap_err();
+    assert_eq!(err.kind(), "addrParse");
+}
+
+#[tokio::test]
+async fn broadcast_reaches_every_live_port() {
+    let mock = listeners(vec![MockBehavior::default(); 3]).await;
+
+    let report = mock.client().execute("print(2)", &Target::All).await.unwrap();
+
+    assert_eq!(report.target, "ALL");
+    assert_eq!(report.ports.len(), 3);
+    assert_eq!(report.success_ports(), mock.ports().iter().map(String::as_str).collect::<Vec<_>>());
+    assert_eq!(report.message(), format!("Successfully executed on ports: {}", mock.ports().join(", ")));
+
+    let received = mock.wait_for(3, WAIT).await;
+    assert_eq!(received.len(), 3);
+    assert!(received.iter().all(|r| r.script == "print(2)"));
+}
+
+#[tokio::test]
+async fn broadcast_with_dead_ports_reports_each_port() {
+    let mock = listeners(vec![MockBehavior::refuse(), MockBehavior::default(), MockBehavior::refuse()]).await;
+    let live = mock.ports()[1].clone();
+
+    let report = mock.client().execute("print(3)", &Target::All).await.unwrap();
+
+    assert!(report.is_success());
+    assert_eq!(report.success_ports(), vec![live.as_str()]);
+    for (port, entry) in mock.ports().iter().zip(&report.ports) {
+        assert_eq!(&entry.port, port);
+        if *port == live {
+            assert!(entry.connected);
+            assert!(entry.error.is_none());
+            assert_eq!(entry.bytes_sent, entry.compressed_size);
+        } else {
+            assert!(!entry.connected);
+            assert_eq!(entry.bytes_sent, 0);
+            assert_eq!(entry.error, Some(PotassiumError::ConnectRefused { port: port.clone() }));
+        }
+    }
+    assert_eq!(mock.wait_for(1, WAIT).await.len(), 1);
+}
+
+#[tokio::test]
+async fn all_ports_dead_reports_last_error() {
+    let mock = listeners(vec![MockBehavior::refuse(), MockBehavior::refuse()]).await;
+    let last = mock.ports()[1].clone();
+
+    let report = mock.client().execute("print(4)", &Target::All).await.unwrap();
+
+    assert!(!report.is_success());
+    assert!(report.success_ports().is_empty());
+    assert_eq!(
+        report.message(),
+        format!(
+            "Failed to connect on all ports. Last error: Failed to connect to port {}: connection refused",
+            last
+        )
+    );
+}
+
+#[tokio::test]
+async fn invalid_port_is_rejected_before_connecting() {
+    let mock = listeners(vec![MockBehavior::default()]).await;
+
+    let err = mock.client().execute("print(5)", &Target::Port("0".into())).await.unwrap_err();
+    assert_eq!(err, PotassiumError::InvalidPort { port: "0".into() });
+    assert_eq!(err.kind(), "invalidPort");
+
+    let err = mock.client().execute("print(5)", &Target::Port("nope".into())).await.unwrap_err();
+    assert_eq!(err.to_string(), "Invalid port: nope");
+}
+
+#[tokio::test]
+async fn null_is_sent_like_any_other_script() {
+    let mock = listeners(vec![MockBehavior::default()]).await;
+    let port = mock.ports()[0].clone();
+
+    let report = mock.client().execute("NULL", &Target::Port(port)).await.unwrap();
+
+    assert!(!report.probe);
+    assert!(report.ports[0].bytes_sent > 0);
+    assert_eq!(mock.wait_for(1, WAIT).await[0].script, "NULL");
+}
+
+#[tokio::test]
+async fn probe_connects_without_sending() {
+    let mock = listeners(vec![MockBehavior::default(), MockBehavior::refuse()]).await;
+
+    let report = mock.client().probe_target(&Target::All).await.unwrap();
+
+    assert!(report.probe);
+    assert_eq!(report.success_ports(), vec![mock.ports()[0].as_str()]);
+    assert!(report.ports.iter().all(|p| p.bytes_sent == 0));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(mock.received().is_empty());
+}
+
+#[tokio::test]
+async fn attach_any_picks_first_live_port_in_list_order() {
+    let mock = listeners(vec![MockBehavior::refuse(), MockBehavior::default(), MockBehavior::default()]).await;
+
+    assert_eq!(mock.client().attach_any().await.unwrap(), mock.ports()[1]);
+
+    let dead = listeners(vec![MockBehavior::refuse()]).await;
+    assert_eq!(dead.client().attach_any().await.unwrap_err(), PotassiumError::NoInstanceFound);
+}
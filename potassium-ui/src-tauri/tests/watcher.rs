@@ -0,0 +1,36 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use notify::RecursiveMode;
+use potassium_ui_lib::watcher::ScriptWatcher;
+use tempfile::TempDir;
+
+#[test]
+fn a_burst_of_writes_is_reported_once() {
+    let tmp = TempDir::new().unwrap();
+    let dir = fs::canonicalize(tmp.path()).unwrap();
+    let file = dir.join("script.lua");
+    fs::write(&file, "print(1)").unwrap();
+
+    let (tx, rx) = mpsc::channel();
+    let watcher = ScriptWatcher::new(move |path| {
+        let _ = tx.send(path);
+    })
+    .unwrap();
+    watcher.watch(BTreeMap::from([(dir.clone(), RecursiveMode::NonRecursive)]));
+    assert_eq!(watcher.watched(), std::slice::from_ref(&dir));
+
+    for i in 0..5 {
+        fs::write(&file, format!("print({})", i)).unwrap();
+    }
+
+    assert_eq!(rx.recv_timeout(Duration::from_secs(2)).unwrap(), file);
+    assert!(rx.recv_timeout(Duration::from_millis(400)).is_err());
+
+    watcher.watch(BTreeMap::new());
+    assert!(watcher.watched().is_empty());
+    fs::write(&file, "print('unwatched')").unwrap();
+    assert!(rx.recv_timeout(Duration::from_millis(400)).is_err());
+}
@@ -0,0 +1,153 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use potassium_ui_lib::error::PotassiumError;
+use potassium_ui_lib::workspace::{Workspace, SCRATCH_DIR, WORKSPACE_FILE};
+use tempfile::TempDir;
+
+fn workspace(root: &Path) -> Workspace {
+    Workspace::load(root.join("scripts"), root.join("config").join(WORKSPACE_FILE))
+}
+
+#[test]
+fn tabs_are_files_and_survive_a_reload() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    let ws = workspace(root);
+
+    let first = ws.create("Untitled Tab 1", "print(1)").unwrap();
+    let second = ws.create("Untitled Tab 1", "print(2)").unwrap();
+    assert_eq!(first.tab.name, "Untitled Tab 1.lua");
+    assert_eq!(second.tab.name, "Untitled Tab 1 (2).lua");
+    assert_eq!(fs::read_to_string(root.join("scripts/Untitled Tab 1.lua")).unwrap(), "print(1)");
+    ws.set_active(&first.tab.id).unwrap();
+
+    let reloaded = workspace(root).snapshot();
+    assert_eq!(reloaded.active.as_deref(), Some(first.tab.id.as_str()));
+    let contents: Vec<&str> = reloaded.tabs.iter().map(|t| t.content.as_str()).collect();
+    assert_eq!(contents, ["print(1)", "print(2)"]);
+}
+
+#[test]
+fn edits_are_dirty_until_saved() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    let ws = workspace(root);
+    let tab = ws.create("a", "x = 1").unwrap().tab;
+    assert!(!tab.dirty);
+
+    assert!(ws.update(&tab.id, "x = 2").unwrap().dirty);
+    assert_eq!(fs::read_to_string(&tab.path).unwrap(), "x = 1");
+    assert!(!ws.update(&tab.id, "x = 1").unwrap().dirty);
+
+    assert!(!ws.save(&tab.id, "x = 3").unwrap().dirty);
+    assert_eq!(fs::read_to_string(&tab.path).unwrap(), "x = 3");
+}
+
+#[test]
+fn unsaved_edits_survive_a_reload() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    let ws = workspace(root);
+    let tab = ws.create("a", "x = 1").unwrap().tab;
+    ws.update(&tab.id, "x = 2").unwrap();
+
+    let reloaded = workspace(root).snapshot();
+    assert_eq!(reloaded.tabs[0].content, "x = 2");
+    assert!(reloaded.tabs[0].tab.dirty);
+    assert_eq!(fs::read_to_string(&tab.path).unwrap(), "x = 1");
+
+    ws.save(&tab.id, "x = 2").unwrap();
+    assert!(!root.join("config").join(SCRATCH_DIR).read_dir().unwrap().any(|_| true));
+    assert!(!workspace(root).snapshot().tabs[0].tab.dirty);
+}
+
+#[test]
+fn rename_moves_the_file_and_refuses_to_overwrite() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    let ws = workspace(root);
+    let a = ws.create("a", "a").unwrap().tab;
+    let b = ws.create("b", "b").unwrap().tab;
+
+    let renamed = ws.rename(&a.id, "main").unwrap();
+    assert_eq!(renamed.name, "main.lua");
+    assert!(!PathBuf::from(&a.path).exists());
+    assert_eq!(fs::read_to_string(&renamed.path).unwrap(), "a");
+
+    assert!(matches!(ws.rename(&b.id, "main.lua"), Err(PotassiumError::File { .. })));
+    assert_eq!(ws.rename(&b.id, "B.lua").unwrap().name, "B.lua");
+    assert!(matches!(ws.rename(&b.id, "../escape"), Err(PotassiumError::File { .. })));
+    assert_eq!(fs::read_to_string(&renamed.path).unwrap(), "a");
+}
+
+#[test]
+fn open_and_save_as_remember_paths() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    let outside = root.join("elsewhere");
+    fs::create_dir_all(&outside).unwrap();
+    fs::write(outside.join("lib.lua"), "return {}").unwrap();
+    let ws = workspace(root);
+
+    let opened = ws.open(&outside.join("lib.lua")).unwrap();
+    assert_eq!(opened.content, "return {}");
+    assert_eq!(ws.open(&outside.join("lib.lua")).unwrap().tab.id, opened.tab.id);
+    assert_eq!(ws.last_dir(), fs::canonicalize(&outside).unwrap());
+
+    let tab = ws.create("draft", "print('x')").unwrap().tab;
+    let target = outside.join("final.lua");
+    let saved = ws.save_as(&tab.id, "print('y')", &target).unwrap();
+    assert_eq!(PathBuf::from(&saved.path), fs::canonicalize(&target).unwrap());
+    ws.save(&tab.id, "print('z')").unwrap();
+    assert_eq!(fs::read_to_string(&target).unwrap(), "print('z')");
+
+    ws.close(&opened.tab.id).unwrap();
+    let paths: Vec<String> = workspace(root).snapshot().tabs.into_iter().map(|t| t.tab.path).collect();
+    assert_eq!(paths, [saved.path]);
+}
+
+#[test]
+fn save_as_refuses_a_path_open_in_another_tab() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    let ws = workspace(root);
+    let kept = ws.create("kept", "a = 1").unwrap().tab;
+    let other = ws.create("other", "b = 1").unwrap().tab;
+
+    let taken = ws.save_as(&other.id, "b = 2", Path::new(&kept.path));
+    assert!(matches!(taken, Err(PotassiumError::File { .. })));
+    assert_eq!(fs::read_to_string(&kept.path).unwrap(), "a = 1");
+
+    // Saving a tab over its own file is fine.
+    let own = ws.save_as(&kept.id, "a = 2", Path::new(&kept.path)).unwrap();
+    assert_eq!(own.path, kept.path);
+}
+
+#[test]
+fn outside_edits_reload_clean_tabs_and_flag_dirty_ones() {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path();
+    let ws = workspace(root);
+    let clean = ws.create("clean", "a = 1").unwrap().tab;
+    let edited = ws.create("edited", "b = 1").unwrap().tab;
+    ws.update(&edited.id, "b = 2").unwrap();
+
+    // Our own save is not a change.
+    ws.save(&clean.id, "a = 2").unwrap();
+    assert_eq!(ws.sync_from_disk(Path::new(&clean.path)), None);
+
+    fs::write(&clean.path, "a = 3").unwrap();
+    let change = ws.sync_from_disk(Path::new(&clean.path)).unwrap();
+    assert!(!change.conflict && !change.tab.dirty);
+    assert_eq!(change.content, "a = 3");
+
+    fs::write(&edited.path, "b = 3").unwrap();
+    let change = ws.sync_from_disk(Path::new(&edited.path)).unwrap();
+    assert!(change.conflict && change.tab.dirty);
+    let buffers: Vec<String> = ws.snapshot().tabs.into_iter().map(|t| t.content).collect();
+    assert_eq!(buffers, ["a = 3", "b = 2"]);
+
+    assert!(ws.set_execute_on_save(&clean.id, true).unwrap().execute_on_save);
+    assert!(workspace(root).snapshot().tabs[0].tab.execute_on_save);
+}